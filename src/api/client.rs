@@ -6,45 +6,103 @@ use std::time::Duration;
 
 use reqwest::{self, Client};
 use serde::{self, Deserialize, Serialize};
-use serde_json;
+use serde_json::{self, json, Value};
 use uuid::Uuid;
 
 use crate::api::resource::{
-    CommandResource, Filter, Item, Label, Note, Project, ProjectNote, Reminder, Resource,
+    ActivityEvent, ActivityQuery, Collaborator, CollaboratorState, CommandResource, CompletedItem,
+    Filter, Item, Label, LiveNotification, Note, ProductivityStats, Project, ProjectNote, Reminder,
+    Resource, Section, User, UserSettings,
 };
 
+const SYNC_API_URL: &str = "https://api.todoist.com/sync/v8/sync";
+// The sync API rejects requests with more commands than this
+pub const MAX_COMMANDS: usize = 100;
+// completed/get_all returns at most this many tasks per request
+const COMPLETED_LIMIT: usize = 200;
+// and activity/get this many events
+const ACTIVITY_LIMIT: usize = 100;
+
 pub struct TodoistClient {
     token: String,
     client: Client,
     url: String,
+    // Commands are built but not sent, see TodoistApiError::DryRun
+    dry_run: bool,
+}
+
+impl fmt::Debug for TodoistClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TodoistClient")
+            .field("token", &REDACTED)
+            .field("url", &self.url)
+            .field("dry_run", &self.dry_run)
+            .finish()
+    }
 }
 
 impl TodoistClient {
     pub fn new(token: &str) -> TodoistClient {
+        TodoistClient::with_url(token, SYNC_API_URL)
+    }
+
+    // Allows pointing the client to a different sync endpoint, like a proxy or a mock server
+    pub fn with_url(token: &str, url: &str) -> TodoistClient {
         let timeout = Duration::new(10, 0);
         TodoistClient {
             token: token.to_owned(),
             client: Client::new(),
-            url: "https://api.todoist.com/sync/v8/sync".to_owned(),
+            url: url.to_owned(),
+            dry_run: false,
         }
     }
 
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
     #[tokio::main]
     pub async fn get_resources(
         &self,
         resources: Vec<&str>,
+    ) -> Result<TodoistResponse, TodoistApiError> {
+        self.fetch(resources, None).await
+    }
+
+    // Resources changed since the sync_token of an earlier response, or every
+    // resource for the token "*". Deleted ones have is_deleted set.
+    #[tokio::main]
+    pub async fn get_changes(
+        &self,
+        resources: Vec<&str>,
+        sync_token: &str,
+    ) -> Result<TodoistResponse, TodoistApiError> {
+        self.fetch(resources, Some(sync_token)).await
+    }
+
+    async fn fetch(
+        &self,
+        resources: Vec<&str>,
+        sync_token: Option<&str>,
     ) -> Result<TodoistResponse, TodoistApiError> {
         let mut builder = TodoistQueryBuilder::new(&self.token);
         for resource in resources.into_iter() {
             builder.get(&resource);
         }
+        if let Some(sync_token) = sync_token {
+            builder.sync_token(sync_token);
+        }
         let query = builder.build();
 
         debug!("Sending query: {:?}", query);
         let response: TodoistResponse = self
             .client
-            .get(&self.url)
-            .query(&query)
+            .post(&self.url)
+            .form(&query)
             .send()
             .await?
             .json()
@@ -54,26 +112,160 @@ impl TodoistClient {
         Ok(response)
     }
 
+    // Endpoints other than sync are next to it, like completed/get_all
+    fn endpoint(&self, path: &str) -> String {
+        match self.url.rfind('/') {
+            Some(index) => format!("{}/{}", &self.url[..index], path),
+            None => path.to_string(),
+        }
+    }
+
+    // Archived projects are left out of syncs
+    #[tokio::main]
+    pub async fn get_archived_projects(&self) -> Result<Vec<Project>, TodoistApiError> {
+        let projects: Vec<Project> = self
+            .client
+            .post(&self.endpoint("projects/get_archived"))
+            .form(&[("token", &self.token)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        debug!("API response: {:?}", projects);
+        Ok(projects)
+    }
+
+    // The project with its sections, open tasks and comments, which syncs
+    // leave out for archived projects
+    #[tokio::main]
+    pub async fn get_project_data(&self, project_id: u32) -> Result<ProjectData, TodoistApiError> {
+        let data: ProjectData = self
+            .client
+            .post(&self.endpoint("projects/get_data"))
+            .form(&[
+                ("token", self.token.clone()),
+                ("project_id", project_id.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        debug!("API response: {:?}", data);
+        Ok(data)
+    }
+
+    // Completed tasks, most recent first, fetched a page at a time. Dates
+    // are UTC, like 2020-01-31T09:00. Only premium users have access to them.
     #[tokio::main]
-    pub async fn sync_resources<T>(
+    pub async fn get_completed(
         &self,
-        resources: Vec<T>,
-    ) -> Result<TodoistResponse, TodoistApiError>
-    where
-        T: CommandResource + Resource,
-    {
-        let mut builder = TodoistQueryBuilder::new(&self.token);
-        for resource in resources.into_iter() {
-            // TODO: uuid and temp_id should not be hardcoded to None
-            builder.add(&resource, None, None);
+        project_id: Option<u32>,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<CompletedItem>, TodoistApiError> {
+        let mut items = Vec::new();
+        loop {
+            let mut params = vec![
+                ("token", self.token.clone()),
+                ("limit", COMPLETED_LIMIT.to_string()),
+                ("offset", items.len().to_string()),
+            ];
+            if let Some(project_id) = project_id {
+                params.push(("project_id", project_id.to_string()));
+            }
+            if let Some(since) = since {
+                params.push(("since", since.to_string()));
+            }
+            if let Some(until) = until {
+                params.push(("until", until.to_string()));
+            }
+
+            let response: CompletedResponse = self
+                .client
+                .post(&self.endpoint("completed/get_all"))
+                .form(&params)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            debug!("API response: {:?}", response);
+
+            let count = response.items.len();
+            items.extend(response.items);
+            if count < COMPLETED_LIMIT {
+                return Ok(items);
+            }
         }
-        let query = builder.build();
+    }
+
+    // Events of the page of the query, most recent first, fetched a request
+    // at a time
+    #[tokio::main]
+    pub async fn get_activity(
+        &self,
+        query: &ActivityQuery,
+    ) -> Result<Vec<ActivityEvent>, TodoistApiError> {
+        let mut events = Vec::new();
+        loop {
+            let response: ActivityResponse = self
+                .client
+                .post(&self.endpoint("activity/get"))
+                .query(query)
+                .form(&[
+                    ("token", self.token.clone()),
+                    ("limit", ACTIVITY_LIMIT.to_string()),
+                    ("offset", events.len().to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            debug!("API response: {:?}", response);
 
+            let count = response.events.len();
+            events.extend(response.events);
+            if count < ACTIVITY_LIMIT || events.len() >= response.count {
+                return Ok(events);
+            }
+        }
+    }
+
+    #[tokio::main]
+    pub async fn get_stats(&self) -> Result<ProductivityStats, TodoistApiError> {
+        let stats: ProductivityStats = self
+            .client
+            .post(&self.endpoint("completed/get_stats"))
+            .form(&[("token", &self.token)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        debug!("API response: {:?}", stats);
+        Ok(stats)
+    }
+
+    pub fn builder(&self) -> TodoistQueryBuilder {
+        TodoistQueryBuilder::new(&self.token)
+    }
+
+    #[tokio::main]
+    pub async fn sync(&self, query: &TodoistQuery) -> Result<TodoistResponse, TodoistApiError> {
+        if self.dry_run {
+            return Err(TodoistApiError::DryRun(query.clone()));
+        }
         debug!("Sending query: {:?}", query);
         let response: TodoistResponse = self
             .client
             .post(&self.url)
-            .query(&query)
+            .form(query)
             .send()
             .await?
             .json()
@@ -83,13 +275,140 @@ impl TodoistClient {
 
         Ok(response)
     }
+
+    pub fn sync_resources<T>(&self, resources: Vec<T>) -> Result<TodoistResponse, TodoistApiError>
+    where
+        T: CommandResource + Resource,
+    {
+        let mut builder = self.builder();
+        for resource in resources.into_iter() {
+            // TODO: uuid and temp_id should not be hardcoded to None
+            builder.add(&resource, None, None);
+        }
+        self.sync(&builder.build())
+    }
+
+    // Sends the commands in as few requests as the API allows. The result of
+    // each command is returned in the order given: the id of the resource it
+    // created, if any, or the error reported by the API. Only a failure of the
+    // first request fails them all.
+    pub fn sync_batch<T>(&self, resources: &[T]) -> Result<Vec<CommandResult>, TodoistApiError>
+    where
+        T: CommandResource + Resource,
+    {
+        // All commands are shown at once, instead of the first request only
+        if self.dry_run {
+            let mut builder = self.builder();
+            for resource in resources.iter() {
+                builder.add(resource, None, None);
+            }
+            return Err(TodoistApiError::DryRun(builder.build()));
+        }
+
+        // Temp ids only resolve within a request, so later requests get the
+        // ids the earlier ones were mapped to instead
+        let mut mapping: Map<String, u32> = Map::new();
+        let mut results = Vec::with_capacity(resources.len());
+        for chunk in resources.chunks(MAX_COMMANDS) {
+            let mut builder = self.builder();
+            let ids: Vec<(Uuid, Uuid)> = chunk
+                .iter()
+                .map(|resource| {
+                    let uuid = Uuid::new_v4();
+                    let temp_id = resource.temp_id().unwrap_or_else(Uuid::new_v4);
+                    let args = replace_temp_ids(resource.to_json(), &mapping);
+                    builder.push(
+                        &resource.command(),
+                        args,
+                        &resource.resource(),
+                        Some(uuid),
+                        Some(temp_id),
+                    );
+                    (uuid, temp_id)
+                })
+                .collect();
+            // Commands sent before a failed request are done and kept, the
+            // ones of that request and after it fail with its error
+            let response = match self.sync(&builder.build()) {
+                Ok(response) => response,
+                Err(err) if results.is_empty() => return Err(err),
+                Err(err) => {
+                    let error = err.to_string();
+                    results.resize(resources.len(), Err(error));
+                    return Ok(results);
+                }
+            };
+
+            for (uuid, temp_id) in ids.into_iter() {
+                let status = response
+                    .sync_status
+                    .as_ref()
+                    .and_then(|s| s.get(&uuid.to_string()));
+                results.push(match status {
+                    Some(SyncStatus::Error { error, .. }) => Err(error.clone()),
+                    _ => Ok(response.temp_id_mapping.get(&temp_id.to_string()).cloned()),
+                });
+            }
+            mapping.extend(response.temp_id_mapping.into_iter());
+        }
+        Ok(results)
+    }
+}
+
+// Replaces the temp ids in the arguments of a command by the ids they were
+// mapped to
+pub fn replace_temp_ids(args: Value, mapping: &Map<String, u32>) -> Value {
+    match args {
+        Value::String(s) => match mapping.get(&s) {
+            Some(id) => Value::from(*id),
+            None => Value::String(s),
+        },
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| replace_temp_ids(v, mapping))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, replace_temp_ids(v, mapping)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+pub type CommandResult = Result<Option<u32>, String>;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ActivityResponse {
+    pub events: Vec<ActivityEvent>,
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CompletedResponse {
+    pub items: Vec<CompletedItem>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectData {
+    pub project: Project,
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    #[serde(default)]
+    pub items: Vec<Item>,
+    #[serde(default)]
+    pub project_notes: Vec<ProjectNote>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct TodoistResponse {
     #[serde(default)]
     pub projects: Option<Vec<Project>>,
     #[serde(default)]
+    pub sections: Option<Vec<Section>>,
+    #[serde(default)]
     pub items: Option<Vec<Item>>,
     #[serde(default)]
     pub notes: Option<Vec<Note>>,
@@ -101,25 +420,109 @@ pub struct TodoistResponse {
     pub project_notes: Option<Vec<ProjectNote>>,
     #[serde(default)]
     pub reminders: Option<Vec<Reminder>>,
+    // Collaborators come with their states in shared projects
+    #[serde(default)]
+    pub collaborators: Option<Vec<Collaborator>>,
+    #[serde(default)]
+    pub collaborator_states: Option<Vec<CollaboratorState>>,
+    #[serde(default)]
+    pub user: Option<User>,
+    #[serde(default)]
+    pub live_notifications: Option<Vec<LiveNotification>>,
+    #[serde(default)]
+    pub live_notifications_last_read_id: Option<u32>,
+    #[serde(default)]
+    pub user_settings: Option<UserSettings>,
     pub full_sync: bool,
     pub temp_id_mapping: Map<String, u32>,
+    #[serde(default)]
+    pub sync_status: Option<Map<String, SyncStatus>>,
     pub sync_token: String,
 }
 
-#[derive(Debug, Serialize)]
+impl TodoistResponse {
+    // Commands that failed, as pairs of command uuid and error
+    pub fn errors(&self) -> Vec<(&str, &str)> {
+        match &self.sync_status {
+            Some(status) => status
+                .iter()
+                .filter_map(|(uuid, s)| match s {
+                    SyncStatus::Error { error, .. } => Some((uuid.as_str(), error.as_str())),
+                    SyncStatus::Ok(_) => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+// Result of each command sent, keyed by command uuid in TodoistResponse::sync_status
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SyncStatus {
+    Ok(String),
+    Error { error_code: i32, error: String },
+}
+
+// Sent as a form in the body of POST requests, keeping the token and commands
+// out of URLs, which end up in logs and error messages. Lists are JSON encoded.
+#[derive(Clone, Serialize)]
 pub struct TodoistQuery {
     token: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     sync_token: Option<String>,
-    resource_types: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    commands: Option<String>,
+    #[serde(serialize_with = "as_json_string")]
+    resource_types: Vec<String>,
+    #[serde(serialize_with = "as_json_string")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    commands: Vec<TodoistCommand>,
+}
+
+fn as_json_string<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: serde::Serializer,
+{
+    match serde_json::to_string(value) {
+        Ok(json) => serializer.serialize_str(&json),
+        Err(err) => Err(serde::ser::Error::custom(err)),
+    }
+}
+
+impl TodoistQuery {
+    pub fn commands(&self) -> &[TodoistCommand] {
+        &self.commands
+    }
+
+    // The query as it would be sent, safe to print
+    pub fn redacted(&self) -> serde_json::Value {
+        json!({
+            "token": REDACTED,
+            "sync_token": self.sync_token,
+            "resource_types": self.resource_types,
+            "commands": self.commands,
+        })
+    }
+}
+
+// Tokens are never printed, so queries can be logged safely
+const REDACTED: &str = "[REDACTED]";
+
+impl fmt::Debug for TodoistQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TodoistQuery")
+            .field("token", &REDACTED)
+            .field("sync_token", &self.sync_token)
+            .field("resource_types", &self.resource_types)
+            .field("commands", &self.commands)
+            .finish()
+    }
 }
 
 // Represents a sync API command
 // uuid uniquely identifies the request to allow for safe retries in case of failure
 // temp_id assigns an id to a new object that can be referenced by other objects created in the same request
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TodoistCommand {
     #[serde(rename = "type")]
     command_string: String,
@@ -129,13 +532,42 @@ pub struct TodoistCommand {
     temp_id: Uuid,
 }
 
+impl TodoistCommand {
+    pub fn command(&self) -> &str {
+        &self.command_string
+    }
+
+    pub fn args(&self) -> &serde_json::Value {
+        &self.args
+    }
+
+    pub fn uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    pub fn temp_id(&self) -> &Uuid {
+        &self.temp_id
+    }
+}
+
 pub struct TodoistQueryBuilder {
     token: String,
-    commands: Vec<String>,
+    commands: Vec<TodoistCommand>,
     sync_token: Option<String>,
     resource_types: Vec<String>,
 }
 
+impl fmt::Debug for TodoistQueryBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TodoistQueryBuilder")
+            .field("token", &REDACTED)
+            .field("commands", &self.commands)
+            .field("sync_token", &self.sync_token)
+            .field("resource_types", &self.resource_types)
+            .finish()
+    }
+}
+
 impl TodoistQueryBuilder {
     pub fn new(token: &str) -> TodoistQueryBuilder {
         TodoistQueryBuilder {
@@ -151,17 +583,33 @@ impl TodoistQueryBuilder {
         resource: &T,
         uuid: Option<Uuid>,
         temp_id: Option<Uuid>,
+    ) -> &'a mut TodoistQueryBuilder {
+        let temp_id = temp_id.or(resource.temp_id());
+        self.push(
+            &resource.command(),
+            resource.to_json(),
+            &resource.resource(),
+            uuid,
+            temp_id,
+        )
+    }
+
+    fn push<'a>(
+        &'a mut self,
+        command: &str,
+        args: Value,
+        resource_type: &str,
+        uuid: Option<Uuid>,
+        temp_id: Option<Uuid>,
     ) -> &'a mut TodoistQueryBuilder {
         let command = TodoistCommand {
-            command_string: resource.command(),
-            args: resource.to_json(),
+            command_string: command.to_string(),
+            args: args,
             uuid: uuid.map_or(Uuid::new_v4(), |u| u.into()),
             temp_id: temp_id.map_or(Uuid::new_v4(), |u| u.into()),
         };
-        if let Ok(c) = serde_json::to_string(&command) {
-            self.commands.push(c);
-        }
-        self.resource_types.push(resource.resource());
+        self.commands.push(command);
+        self.resource_types.push(resource_type.to_string());
         self
     }
 
@@ -170,15 +618,22 @@ impl TodoistQueryBuilder {
         self
     }
 
+    // Only changes since the given sync_token will be returned, instead of a full sync
+    pub fn sync_token<'a>(&'a mut self, sync_token: &str) -> &'a mut TodoistQueryBuilder {
+        self.sync_token = Some(sync_token.to_string());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
     pub fn build(&self) -> TodoistQuery {
         TodoistQuery {
             token: self.token.clone(),
             sync_token: self.sync_token.clone(),
-            resource_types: serde_json::to_string(&self.resource_types).unwrap(),
-            commands: match self.commands.len() {
-                0 => None,
-                _ => Some(serde_json::to_string(&self.commands).unwrap()),
-            },
+            resource_types: self.resource_types.clone(),
+            commands: self.commands.clone(),
         }
     }
 }
@@ -187,6 +642,9 @@ impl TodoistQueryBuilder {
 pub enum TodoistApiError {
     DeserializeError(serde_json::error::Error),
     RequestError(reqwest::Error),
+    // Returned instead of a response by clients in dry run mode, with the
+    // query that would have been sent
+    DryRun(TodoistQuery),
 }
 
 impl From<serde_json::error::Error> for TodoistApiError {
@@ -203,10 +661,11 @@ impl From<reqwest::Error> for TodoistApiError {
 
 impl Error for TodoistApiError {
     fn cause(&self) -> Option<&Error> {
-        Some(match *self {
-            TodoistApiError::DeserializeError(ref err) => err as &Error,
-            TodoistApiError::RequestError(ref err) => err as &Error,
-        })
+        match *self {
+            TodoistApiError::DeserializeError(ref err) => Some(err as &Error),
+            TodoistApiError::RequestError(ref err) => Some(err as &Error),
+            TodoistApiError::DryRun(_) => None,
+        }
     }
 }
 
@@ -215,6 +674,36 @@ impl fmt::Display for TodoistApiError {
         match *self {
             TodoistApiError::DeserializeError(ref err) => fmt::Display::fmt(err, f),
             TodoistApiError::RequestError(ref err) => fmt::Display::fmt(err, f),
+            TodoistApiError::DryRun(ref query) => {
+                write!(f, "dry run, {} commands not sent", query.commands.len())
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_mapped_temp_ids() {
+        let mut mapping = Map::new();
+        mapping.insert("temp-project".to_string(), 7);
+        mapping.insert("temp-label".to_string(), 9);
+        let args = json!({
+            "content": "Plan",
+            "project_id": "temp-project",
+            "labels": [3, "temp-label", "other"],
+            "due": { "string": "today" },
+        });
+        assert_eq!(
+            replace_temp_ids(args, &mapping),
+            json!({
+                "content": "Plan",
+                "project_id": 7,
+                "labels": [3, 9, "other"],
+                "due": { "string": "today" },
+            })
+        );
+    }
+}
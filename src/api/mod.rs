@@ -1,2 +1,4 @@
 pub mod client;
+pub mod resolve;
 pub mod resource;
+pub mod webhook;
@@ -0,0 +1,308 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::api::resource::{Collaborator, Filter, Item, Label, Project, Section};
+
+// Maps what users type to resource ids. Queries are matched, in order, as ids,
+// exact names, case-insensitive names, case-insensitive prefixes and fuzzy
+// subsequences. The first of these that matches anything wins, and it has to
+// match a single resource. Queries made of digits only are matched as ids.
+//
+// Projects and sections can also be referred to by their path, like
+// "Work/Backend" or "Work/Backend/Review", or any trailing part of it.
+pub struct Resolver<'a> {
+    projects: &'a [Project],
+    sections: &'a [Section],
+    labels: &'a [Label],
+    filters: &'a [Filter],
+    items: &'a [Item],
+    collaborators: &'a [Collaborator],
+}
+
+// Resources are named by a path with a single segment unless they are nested
+struct Candidate {
+    id: u32,
+    path: Vec<String>,
+}
+
+impl Candidate {
+    fn new(id: u32, path: Vec<String>) -> Self {
+        Candidate { id: id, path: path }
+    }
+
+    // The last segments of the path, as many as the query has
+    fn key(&self, segments: usize) -> Option<String> {
+        match self.path.len() >= segments {
+            true => Some(self.path[self.path.len() - segments..].join("/")),
+            false => None,
+        }
+    }
+}
+
+// A resolved resource, matched either by id or by name, ignoring case, or
+// by prefix or fuzzily, which callers may want confirmed
+pub struct Match {
+    pub id: u32,
+    pub exact: bool,
+}
+
+impl Match {
+    fn new(id: u32, exact: bool) -> Self {
+        Match {
+            id: id,
+            exact: exact,
+        }
+    }
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(
+        projects: &'a [Project],
+        sections: &'a [Section],
+        labels: &'a [Label],
+        filters: &'a [Filter],
+        items: &'a [Item],
+        collaborators: &'a [Collaborator],
+    ) -> Self {
+        Resolver {
+            projects: projects,
+            sections: sections,
+            labels: labels,
+            filters: filters,
+            items: items,
+            collaborators: collaborators,
+        }
+    }
+
+    pub fn resolve(&self, resource_type: &str, query: &str) -> Result<u32, ResolveError> {
+        Ok(self.lookup(resource_type, query)?.id)
+    }
+
+    pub fn lookup(&self, resource_type: &str, query: &str) -> Result<Match, ResolveError> {
+        match resource_type {
+            "projects" => self.project(query),
+            "sections" => self.section(query),
+            "labels" => self.label(query),
+            "filters" => self.filter(query),
+            "tasks" | "items" => self.item(query),
+            "collaborators" => self.collaborator(query),
+            other => Err(ResolveError::UnknownResource(other.to_string())),
+        }
+    }
+
+    // Queries can use the # prefix of the Todoist quick add syntax
+    pub fn project(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .projects
+            .iter()
+            .map(|p| Candidate::new(p.id, self.project_path(p.id)))
+            .collect();
+        resolve("project", query.trim_start_matches('#'), true, candidates)
+    }
+
+    pub fn section(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .sections
+            .iter()
+            .map(|s| {
+                let mut path = self.project_path(s.project_id);
+                path.push(s.name.clone());
+                Candidate::new(s.id, path)
+            })
+            .collect();
+        resolve("section", query, true, candidates)
+    }
+
+    // Queries can use the @ prefix of the Todoist quick add syntax
+    pub fn label(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .labels
+            .iter()
+            .map(|l| Candidate::new(l.id, vec![l.name.clone()]))
+            .collect();
+        resolve("label", query.trim_start_matches('@'), false, candidates)
+    }
+
+    pub fn filter(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .filters
+            .iter()
+            .map(|f| Candidate::new(f.id, vec![f.name.clone()]))
+            .collect();
+        resolve("filter", query, false, candidates)
+    }
+
+    // Tasks are matched by content, which may contain slashes
+    pub fn item(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .items
+            .iter()
+            .map(|i| Candidate::new(i.id, vec![i.content.clone()]))
+            .collect();
+        resolve("task", query, false, candidates)
+    }
+
+    // Collaborators are matched by email or full name
+    pub fn collaborator(&self, query: &str) -> Result<Match, ResolveError> {
+        let candidates = self
+            .collaborators
+            .iter()
+            .flat_map(|c| {
+                vec![
+                    Candidate::new(c.id, vec![c.email.clone()]),
+                    Candidate::new(c.id, vec![c.full_name.clone()]),
+                ]
+            })
+            .collect();
+        resolve("collaborator", query, false, candidates)
+    }
+
+    // Names of the project and its ancestors, starting from the root
+    pub fn project_path(&self, id: u32) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut current = self.projects.iter().find(|p| p.id == id);
+        while let Some(project) = current {
+            path.insert(0, project.name.clone());
+            // Stop on malformed data instead of looping forever
+            if path.len() > self.projects.len() {
+                break;
+            }
+            current = project
+                .parent_id
+                .and_then(|parent_id| self.projects.iter().find(|p| p.id == parent_id));
+        }
+        path
+    }
+}
+
+fn resolve(
+    kind: &str,
+    query: &str,
+    nested: bool,
+    candidates: Vec<Candidate>,
+) -> Result<Match, ResolveError> {
+    if !query.is_empty() && query.chars().all(|c| c.is_ascii_digit()) {
+        return match query.parse::<u32>() {
+            Ok(id) if candidates.iter().any(|c| c.id == id) => Ok(Match::new(id, true)),
+            _ => Err(ResolveError::NotFound(kind.to_string(), query.to_string())),
+        };
+    }
+
+    let segments = match nested {
+        true => query.split('/').filter(|s| !s.is_empty()).count().max(1),
+        false => 1,
+    };
+    let lowercase_query = query.to_lowercase();
+    let keys: Vec<(&Candidate, String)> = candidates
+        .iter()
+        .filter_map(|c| c.key(segments).map(|k| (c, k)))
+        .collect();
+
+    let stages: [&dyn Fn(&str) -> bool; 3] = [
+        &|key| key == query,
+        &|key| key.to_lowercase() == lowercase_query,
+        &|key| key.to_lowercase().starts_with(&lowercase_query),
+    ];
+    // Names matched whole, whatever their case, are exact
+    for (i, stage) in stages.iter().enumerate() {
+        let matches: Vec<&Candidate> = keys
+            .iter()
+            .filter(|(_, key)| stage(key))
+            .map(|(c, _)| *c)
+            .collect();
+        if !matches.is_empty() {
+            return Ok(Match::new(single(kind, query, matches)?, i < 2));
+        }
+    }
+
+    // Among fuzzy matches, the ones where the query is spread the least win
+    let scored: Vec<(&Candidate, usize)> = keys
+        .iter()
+        .filter_map(|(c, key)| fuzzy_score(&lowercase_query, &key.to_lowercase()).map(|s| (*c, s)))
+        .collect();
+    if let Some(best) = scored.iter().map(|(_, s)| *s).min() {
+        let matches = scored
+            .into_iter()
+            .filter(|(_, s)| *s == best)
+            .map(|(c, _)| c)
+            .collect();
+        return Ok(Match::new(single(kind, query, matches)?, false));
+    }
+
+    Err(ResolveError::NotFound(kind.to_string(), query.to_string()))
+}
+
+// Resources with more than one name may match through several of them
+fn single(kind: &str, query: &str, mut matches: Vec<&Candidate>) -> Result<u32, ResolveError> {
+    matches.dedup_by_key(|c| c.id);
+    match matches.len() {
+        1 => Ok(matches[0].id),
+        _ => Err(ResolveError::Ambiguous(
+            kind.to_string(),
+            query.to_string(),
+            matches
+                .iter()
+                .map(|c| format!("{} ({})", c.path.join("/"), c.id))
+                .collect(),
+        )),
+    }
+}
+
+// Length of the shortest span of key that contains all characters of query in
+// order, or None if query is not a subsequence of key
+pub fn fuzzy_score(query: &str, key: &str) -> Option<usize> {
+    let query: Vec<char> = query.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<usize> = None;
+    for start in (0..key.len()).filter(|i| key[*i] == query[0]) {
+        let mut matched = 0;
+        let mut end = start;
+        for (i, c) in key.iter().enumerate().skip(start) {
+            if *c == query[matched] {
+                matched += 1;
+                end = i;
+                if matched == query.len() {
+                    break;
+                }
+            }
+        }
+        if matched == query.len() {
+            let span = end - start + 1;
+            best = Some(best.map_or(span, |b| b.min(span)));
+        }
+    }
+    best
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    NotFound(String, String),
+    Ambiguous(String, String, Vec<String>),
+    UnknownResource(String),
+}
+
+impl Error for ResolveError {}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolveError::NotFound(ref kind, ref query) => {
+                write!(f, "no {} matches \"{}\"", kind, query)
+            }
+            ResolveError::Ambiguous(ref kind, ref query, ref candidates) => write!(
+                f,
+                "\"{}\" matches more than one {}: {}",
+                query,
+                kind,
+                candidates.join(", ")
+            ),
+            ResolveError::UnknownResource(ref resource) => {
+                write!(f, "cannot resolve {} by name", resource)
+            }
+        }
+    }
+}
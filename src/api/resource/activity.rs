@@ -0,0 +1,76 @@
+use std::collections::BTreeMap as Map;
+
+use serde::{self, Deserialize, Serialize};
+use serde_json::Value;
+
+// An event of the activity log, like a task completed by a collaborator.
+// extra_data depends on the event, like the content of an added task or the
+// previous name of an updated project.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub id: u64,
+    pub object_type: ObjectType,
+    pub object_id: u32,
+    pub event_type: EventType,
+    pub event_date: String,
+    pub parent_project_id: Option<u32>,
+    pub parent_item_id: Option<u32>,
+    // Events of the user themselves have no initiator
+    pub initiator_id: Option<u32>,
+    #[serde(default)]
+    pub extra_data: Map<String, Value>,
+}
+
+impl ActivityEvent {
+    // Tasks and notes have content, projects a name
+    pub fn content(&self) -> Option<&str> {
+        self.extra_data
+            .get("content")
+            .or(self.extra_data.get("name"))
+            .and_then(|c| c.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectType {
+    Item,
+    Note,
+    Project,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventType {
+    Added,
+    Updated,
+    Deleted,
+    Completed,
+    Uncompleted,
+    Archived,
+    Unarchived,
+    Shared,
+    Left,
+    #[serde(other)]
+    Other,
+}
+
+// Filters of activity/get. Pages go back a week each, from 0 for the
+// current one.
+#[derive(Debug, Default, Serialize)]
+pub struct ActivityQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_project_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initiator_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+}
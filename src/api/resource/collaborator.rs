@@ -0,0 +1,137 @@
+use serde::{self, Deserialize, Serialize};
+use serde_json::{self, json};
+
+use super::{CommandResource, Resource};
+
+// Users sharing at least one project with the current user
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Collaborator {
+    pub id: u32,
+    pub email: String,
+    pub full_name: String,
+    pub timezone: Option<String>,
+    pub image_id: Option<String>,
+}
+
+impl Resource for Collaborator {
+    fn resource(&self) -> String {
+        String::from("collaborators")
+    }
+}
+
+// Membership of a collaborator in a shared project, synced along with
+// collaborators
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollaboratorState {
+    pub project_id: u32,
+    pub user_id: u32,
+    pub state: MemberState,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+impl Resource for CollaboratorState {
+    fn resource(&self) -> String {
+        String::from("collaborators")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberState {
+    Active,
+    Invited,
+    #[serde(other)]
+    Other,
+}
+
+// Invites a user by email, who becomes a collaborator once they accept
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShareProject {
+    pub project_id: u32,
+    pub email: String,
+}
+
+impl Resource for ShareProject {
+    fn resource(&self) -> String {
+        String::from("collaborators")
+    }
+}
+
+impl CommandResource for ShareProject {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("share_project")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteCollaborator {
+    pub project_id: u32,
+    pub email: String,
+}
+
+impl Resource for DeleteCollaborator {
+    fn resource(&self) -> String {
+        String::from("collaborators")
+    }
+}
+
+impl CommandResource for DeleteCollaborator {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("delete_collaborator")
+    }
+}
+
+// Invitations are answered with the id and secret of the live notification
+// they came with
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AcceptInvitation {
+    pub invitation_id: u32,
+    pub invitation_secret: String,
+}
+
+impl Resource for AcceptInvitation {
+    fn resource(&self) -> String {
+        String::from("projects")
+    }
+}
+
+impl CommandResource for AcceptInvitation {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("accept_invitation")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RejectInvitation {
+    pub invitation_id: u32,
+    pub invitation_secret: String,
+}
+
+impl Resource for RejectInvitation {
+    fn resource(&self) -> String {
+        String::from("projects")
+    }
+}
+
+impl CommandResource for RejectInvitation {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("reject_invitation")
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{self, Deserialize, Serialize};
+
+use super::Resource;
+
+// A completed task as returned by completed/get_all, which keeps the tasks
+// the sync endpoint no longer returns. id is the id of the completion, the
+// task keeps its own as task_id.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletedItem {
+    pub id: u32,
+    pub task_id: u32,
+    pub user_id: u32,
+    pub project_id: u32,
+    pub section_id: Option<u32>,
+    pub content: String,
+    pub completed_date: String,
+    #[serde(default)]
+    pub note_count: u32,
+    pub meta_data: Option<String>,
+}
+
+impl Resource for CompletedItem {
+    fn resource(&self) -> String {
+        String::from("completed")
+    }
+}
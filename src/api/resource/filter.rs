@@ -2,16 +2,23 @@ use serde::{self, Deserialize, Serialize};
 
 use super::bool_int;
 use super::color::Color;
+use super::Resource;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Filter {
-    id: u32,
-    name: String,
-    query: String,
-    color: Color,
-    item_order: i32,
+    pub id: u32,
+    pub name: String,
+    pub query: String,
+    pub color: Color,
+    pub item_order: i32,
     #[serde(with = "bool_int")]
-    is_deleted: bool,
+    pub is_deleted: bool,
     #[serde(with = "bool_int")]
-    is_favorite: bool,
+    pub is_favorite: bool,
+}
+
+impl Resource for Filter {
+    fn resource(&self) -> String {
+        String::from("filters")
+    }
 }
@@ -12,6 +12,8 @@ pub struct Item {
     pub project_id: u32,
     pub legay_project_id: Option<u32>,
     pub content: String,
+    #[serde(default)]
+    pub description: String,
     pub due: Option<DueDate>,
     pub priority: i32,
     pub parent_id: Option<u32>,
@@ -48,7 +50,9 @@ pub struct AddItem {
     pub project_id: Option<u32>,
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub due: Option<DueDate>,
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<NewDueDate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -90,21 +94,163 @@ impl CommandResource for AddItem {
     }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateItem {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<NewDueDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub collapsed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_by_uid: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responsible_uid: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_order: Option<i32>,
+}
+
+impl Resource for UpdateItem {
+    fn resource(&self) -> String {
+        String::from("items")
+    }
+}
+
+impl CommandResource for UpdateItem {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("item_update")
+    }
+}
+
+// Only one of project_id, section_id or parent_id can be set when moving an item
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveItem {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<u32>,
+}
+
+impl Resource for MoveItem {
+    fn resource(&self) -> String {
+        String::from("items")
+    }
+}
+
+impl CommandResource for MoveItem {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("item_move")
+    }
+}
+
+// Closing an item completes it, or moves it to its next due date if recurring
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloseItem {
+    pub id: u32,
+}
+
+impl Resource for CloseItem {
+    fn resource(&self) -> String {
+        String::from("items")
+    }
+}
+
+impl CommandResource for CloseItem {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("item_close")
+    }
+}
+
+// Unlike CloseItem, completes recurring tasks instead of moving their due
+// date, and allows setting when they were completed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompleteItem {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_completed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub force_history: Option<bool>,
+}
+
+impl Resource for CompleteItem {
+    fn resource(&self) -> String {
+        String::from("items")
+    }
+}
+
+impl CommandResource for CompleteItem {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("item_complete")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteItem {
+    pub id: u32,
+}
+
+impl Resource for DeleteItem {
+    fn resource(&self) -> String {
+        String::from("items")
+    }
+}
+
+impl CommandResource for DeleteItem {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("item_delete")
+    }
+}
+
+// Time based reminders have a due date or an offset from the one of their
+// task, location based ones have the fields of the location instead
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Reminder {
     id: u32,
-    notify_uid: u32,
-    item_uid: u32,
-    service: String,
+    notify_uid: Option<u32>,
+    #[serde(alias = "item_uid")]
+    item_id: u32,
+    service: Option<String>,
     #[serde(rename = "type")]
     type_: String,
-    due: DueDate,
+    due: Option<DueDate>,
     mm_offset: Option<i32>,
-    name: String,
-    loc_lat: String,
-    loc_long: String,
-    loc_trigger: String,
-    radius: i32,
+    name: Option<String>,
+    loc_lat: Option<String>,
+    loc_long: Option<String>,
+    loc_trigger: Option<String>,
+    radius: Option<i32>,
     #[serde(with = "bool_int")]
     is_deleted: bool,
 }
@@ -117,3 +263,31 @@ pub struct DueDate {
     pub lang: String,
     pub is_recurring: bool,
 }
+
+// Due date as sent when adding or updating items: either a date, or a
+// string in natural language that Todoist parses, like "every monday"
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NewDueDate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub string: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+}
+
+impl NewDueDate {
+    pub fn from_string(string: &str) -> Self {
+        NewDueDate {
+            string: Some(string.to_string()),
+            ..NewDueDate::default()
+        }
+    }
+
+    pub fn from_date(date: &str) -> Self {
+        NewDueDate {
+            date: Some(date.to_string()),
+            ..NewDueDate::default()
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use serde::{self, Deserialize, Serialize};
+use serde_json::{self, json};
 
 use super::bool_int;
 use super::color::Color;
-use super::Resource;
+use super::{CommandResource, Resource};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Label {
@@ -21,3 +22,82 @@ impl Resource for Label {
         String::from("labels")
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddLabel {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_order: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub is_favorite: Option<bool>,
+}
+
+impl Resource for AddLabel {
+    fn resource(&self) -> String {
+        String::from("labels")
+    }
+}
+
+impl CommandResource for AddLabel {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("label_add")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateLabel {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_order: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub is_favorite: Option<bool>,
+}
+
+impl Resource for UpdateLabel {
+    fn resource(&self) -> String {
+        String::from("labels")
+    }
+}
+
+impl CommandResource for UpdateLabel {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("label_update")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteLabel {
+    pub id: u32,
+}
+
+impl Resource for DeleteLabel {
+    fn resource(&self) -> String {
+        String::from("labels")
+    }
+}
+
+impl CommandResource for DeleteLabel {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("label_delete")
+    }
+}
@@ -1,16 +1,42 @@
+mod activity;
 mod bool_int;
+mod collaborator;
 mod color;
+mod completed;
 mod filter;
 mod item;
 mod label;
 mod note;
+mod notification;
 mod project;
+mod raw;
+mod section;
+mod stats;
+mod user;
 
+pub use self::activity::{ActivityEvent, ActivityQuery, EventType, ObjectType};
+pub use self::collaborator::{
+    AcceptInvitation, Collaborator, CollaboratorState, DeleteCollaborator, MemberState,
+    RejectInvitation, ShareProject,
+};
+pub use self::color::Color;
+pub use self::completed::CompletedItem;
 pub use self::filter::Filter;
-pub use self::item::{AddItem, DueDate, Item, Reminder};
-pub use self::label::Label;
-pub use self::note::{Note, ProjectNote};
-pub use self::project::{AddProject, Project};
+pub use self::item::{
+    AddItem, CloseItem, CompleteItem, DeleteItem, DueDate, Item, MoveItem, NewDueDate, Reminder,
+    UpdateItem,
+};
+pub use self::label::{AddLabel, DeleteLabel, Label, UpdateLabel};
+pub use self::note::{AddNote, DeleteNote, Note, ProjectNote, UpdateNote};
+pub use self::notification::{
+    LiveNotification, MarkAllNotificationsRead, MarkNotificationRead, NotificationType,
+    NotificationUser,
+};
+pub use self::project::{AddProject, DeleteProject, MoveProject, Project, UpdateProject};
+pub use self::raw::RawCommand;
+pub use self::section::Section;
+pub use self::stats::{CompletedCount, Goals, ProductivityStats, Streak};
+pub use self::user::{TimezoneInfo, User, UserSettings};
 
 pub trait Resource {
     fn resource(&self) -> String;
@@ -19,11 +45,18 @@ pub trait Resource {
 pub trait CommandResource {
     fn to_json(&self) -> serde_json::Value;
     fn command(&self) -> String;
+    // Resources added by a command get a random temp_id unless given one,
+    // for other commands of the same request to refer to them
+    fn temp_id(&self) -> Option<uuid::Uuid> {
+        None
+    }
 }
 
 pub enum AddResource {
     Item(AddItem),
     Project(AddProject),
+    Label(AddLabel),
+    Note(AddNote),
 }
 
 impl Resource for AddResource {
@@ -31,6 +64,8 @@ impl Resource for AddResource {
         match self {
             AddResource::Item(res) => res.resource(),
             AddResource::Project(res) => res.resource(),
+            AddResource::Label(res) => res.resource(),
+            AddResource::Note(res) => res.resource(),
         }
     }
 }
@@ -40,6 +75,8 @@ impl CommandResource for AddResource {
         match self {
             AddResource::Item(add) => add.command(),
             AddResource::Project(add) => add.command(),
+            AddResource::Label(add) => add.command(),
+            AddResource::Note(add) => add.command(),
         }
     }
 
@@ -47,6 +84,8 @@ impl CommandResource for AddResource {
         match self {
             AddResource::Item(add) => add.to_json(),
             AddResource::Project(add) => add.to_json(),
+            AddResource::Label(add) => add.to_json(),
+            AddResource::Note(add) => add.to_json(),
         }
     }
 }
@@ -1,8 +1,9 @@
 use serde::{self, Deserialize, Serialize};
+use serde_json::{self, json};
 use std::collections::BTreeMap as Map;
 
 use super::bool_int;
-use super::Resource;
+use super::{CommandResource, Resource};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Note {
@@ -14,7 +15,7 @@ pub struct Note {
     pub project_id: u32,
     pub legacy_project_id: Option<u32>,
     pub content: String,
-    pub file_attachment: FileAttachment,
+    pub file_attachment: Option<FileAttachment>,
     pub uids_to_notify: Vec<u32>,
     #[serde(with = "bool_int")]
     pub is_deleted: bool,
@@ -34,7 +35,7 @@ pub struct ProjectNote {
     pub posted_uid: u32,
     pub project_id: u32,
     pub content: String,
-    pub file_attachment: FileAttachment,
+    pub file_attachment: Option<FileAttachment>,
     pub uids_to_notify: Vec<u32>,
     #[serde(with = "bool_int")]
     pub is_deleted: bool,
@@ -48,5 +49,72 @@ pub struct FileAttachment {
     pub file_size: u32,
     pub file_type: String,
     pub file_url: String,
-    pub upload_stae: String,
+    pub upload_state: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddNote {
+    pub item_id: u32,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uids_to_notify: Option<Vec<u32>>,
+}
+
+impl Resource for AddNote {
+    fn resource(&self) -> String {
+        String::from("notes")
+    }
+}
+
+impl CommandResource for AddNote {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("note_add")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateNote {
+    pub id: u32,
+    pub content: String,
+}
+
+impl Resource for UpdateNote {
+    fn resource(&self) -> String {
+        String::from("notes")
+    }
+}
+
+impl CommandResource for UpdateNote {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("note_update")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteNote {
+    pub id: u32,
+}
+
+impl Resource for DeleteNote {
+    fn resource(&self) -> String {
+        String::from("notes")
+    }
+}
+
+impl CommandResource for DeleteNote {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("note_delete")
+    }
 }
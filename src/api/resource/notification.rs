@@ -0,0 +1,106 @@
+use serde::{self, Deserialize, Serialize};
+use serde_json::{self, json};
+
+use super::bool_int;
+use super::{CommandResource, Resource};
+
+// Notifications of what collaborators did, and of invitations to share
+// projects. Fields other than the common ones depend on the type.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LiveNotification {
+    pub id: u32,
+    // Seconds since the epoch
+    pub created: i64,
+    pub notification_type: NotificationType,
+    pub notification_key: Option<String>,
+    pub seq_no: u64,
+    #[serde(with = "bool_int")]
+    pub is_unread: bool,
+    #[serde(default, with = "bool_int")]
+    pub is_deleted: bool,
+    pub from_uid: Option<u32>,
+    pub from_user: Option<NotificationUser>,
+    pub project_id: Option<u32>,
+    pub project_name: Option<String>,
+    pub item_id: Option<u32>,
+    pub item_content: Option<String>,
+    pub responsible_uid: Option<u32>,
+    pub note_id: Option<u32>,
+    pub note_content: Option<String>,
+    pub removed_name: Option<String>,
+    pub removed_uid: Option<u32>,
+    // Share invitations are answered with these, see AcceptInvitation
+    pub invitation_id: Option<u32>,
+    pub invitation_secret: Option<String>,
+    pub state: Option<String>,
+}
+
+impl Resource for LiveNotification {
+    fn resource(&self) -> String {
+        String::from("live_notifications")
+    }
+}
+
+// Who sent an invitation, who may not be a collaborator yet
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationUser {
+    pub id: u32,
+    pub email: String,
+    pub full_name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    ShareInvitationSent,
+    ShareInvitationAccepted,
+    ShareInvitationRejected,
+    UserLeftProject,
+    UserRemovedFromProject,
+    ItemAssigned,
+    ItemCompleted,
+    ItemUncompleted,
+    NoteAdded,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkNotificationRead {
+    pub id: u32,
+}
+
+impl Resource for MarkNotificationRead {
+    fn resource(&self) -> String {
+        String::from("live_notifications")
+    }
+}
+
+impl CommandResource for MarkNotificationRead {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("live_notifications_mark_read")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkAllNotificationsRead {}
+
+impl Resource for MarkAllNotificationsRead {
+    fn resource(&self) -> String {
+        String::from("live_notifications")
+    }
+}
+
+impl CommandResource for MarkAllNotificationsRead {
+    fn to_json(&self) -> serde_json::Value {
+        json!({})
+    }
+
+    fn command(&self) -> String {
+        String::from("live_notifications_mark_read_all")
+    }
+}
@@ -88,3 +88,79 @@ impl CommandResource for AddProject {
         String::from("project_add")
     }
 }
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateProject {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub collapsed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "bool_int::optional")]
+    pub is_favorite: Option<bool>,
+}
+
+impl Resource for UpdateProject {
+    fn resource(&self) -> String {
+        String::from("projects")
+    }
+}
+
+impl CommandResource for UpdateProject {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("project_update")
+    }
+}
+
+// A None parent_id moves the project to the root
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveProject {
+    pub id: u32,
+    pub parent_id: Option<u32>,
+}
+
+impl Resource for MoveProject {
+    fn resource(&self) -> String {
+        String::from("projects")
+    }
+}
+
+impl CommandResource for MoveProject {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("project_move")
+    }
+}
+
+// Deleting a project deletes all its sections, items and descendant projects
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteProject {
+    pub id: u32,
+}
+
+impl Resource for DeleteProject {
+    fn resource(&self) -> String {
+        String::from("projects")
+    }
+}
+
+impl CommandResource for DeleteProject {
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn command(&self) -> String {
+        String::from("project_delete")
+    }
+}
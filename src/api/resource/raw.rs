@@ -0,0 +1,67 @@
+use serde::{self, Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{CommandResource, Resource};
+
+// A command with arguments built as JSON, for commands without a resource of
+// their own or whose arguments refer to resources added in the same request,
+// through their temp_id instead of an id
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawCommand {
+    #[serde(rename = "type")]
+    pub command: String,
+    pub args: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_id: Option<Uuid>,
+}
+
+impl RawCommand {
+    pub fn new(command: &str, args: Value) -> Self {
+        RawCommand {
+            command: command.to_string(),
+            args: args,
+            temp_id: None,
+        }
+    }
+
+    // Fields left unset are null in args built from resources, and the API
+    // expects them missing when adding
+    pub fn add(command: &str, args: Value, temp_id: Uuid) -> Self {
+        let args = match args {
+            Value::Object(map) => {
+                Value::Object(map.into_iter().filter(|(_, v)| !v.is_null()).collect())
+            }
+            other => other,
+        };
+        RawCommand {
+            command: command.to_string(),
+            args: args,
+            temp_id: Some(temp_id),
+        }
+    }
+}
+
+impl Resource for RawCommand {
+    // Commands are named after their resource, like item_add or project_move
+    fn resource(&self) -> String {
+        match self.command.split('_').next() {
+            Some(resource) => format!("{}s", resource),
+            None => self.command.clone(),
+        }
+    }
+}
+
+impl CommandResource for RawCommand {
+    fn to_json(&self) -> Value {
+        self.args.clone()
+    }
+
+    fn command(&self) -> String {
+        self.command.clone()
+    }
+
+    fn temp_id(&self) -> Option<Uuid> {
+        self.temp_id
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{self, Deserialize, Serialize};
+
+use super::bool_int;
+use super::Resource;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Section {
+    pub id: u32,
+    pub name: String,
+    pub project_id: u32,
+    pub section_order: i32,
+    #[serde(with = "bool_int")]
+    pub collapsed: bool,
+    pub sync_id: Option<u32>,
+    #[serde(with = "bool_int")]
+    pub is_deleted: bool,
+    #[serde(with = "bool_int")]
+    pub is_archived: bool,
+    pub date_archived: Option<String>,
+    pub date_added: String,
+}
+
+impl Resource for Section {
+    fn resource(&self) -> String {
+        String::from("sections")
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{self, Deserialize, Serialize};
+
+// Productivity of the user as returned by completed/get_stats: karma, tasks
+// completed in the last days and weeks, and goals with their streaks
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProductivityStats {
+    #[serde(default)]
+    pub karma: f64,
+    #[serde(default)]
+    pub karma_trend: String,
+    #[serde(default)]
+    pub completed_count: u32,
+    #[serde(default)]
+    pub days_items: Vec<CompletedCount>,
+    #[serde(default)]
+    pub week_items: Vec<CompletedCount>,
+    #[serde(default)]
+    pub goals: Goals,
+}
+
+// Dates of weeks are ranges, like 2020-01-27/2020-02-02
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletedCount {
+    pub date: String,
+    pub total_completed: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Goals {
+    #[serde(default)]
+    pub daily_goal: u32,
+    #[serde(default)]
+    pub weekly_goal: u32,
+    #[serde(default)]
+    pub current_daily_streak: Streak,
+    #[serde(default)]
+    pub max_daily_streak: Streak,
+    #[serde(default)]
+    pub current_weekly_streak: Streak,
+    #[serde(default)]
+    pub max_weekly_streak: Streak,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Streak {
+    pub count: u32,
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
@@ -0,0 +1,122 @@
+use serde::{self, Deserialize, Serialize};
+
+use super::Resource;
+
+// The user the token belongs to. The API also returns the token itself, which
+// is left out so it is never written to the cache.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct User {
+    pub id: u32,
+    pub email: String,
+    pub full_name: String,
+    pub inbox_project: Option<u32>,
+    pub team_inbox: Option<u32>,
+    #[serde(default)]
+    pub tz_info: TimezoneInfo,
+    // Day the week starts on and days off, 1 is Monday and 7 Sunday
+    #[serde(default = "monday")]
+    pub start_day: u8,
+    #[serde(default = "monday")]
+    pub next_week: u8,
+    #[serde(default)]
+    pub days_off: Vec<u8>,
+    #[serde(default)]
+    pub start_page: String,
+    // 0 for DD-MM-YYYY, 1 for MM-DD-YYYY
+    #[serde(default)]
+    pub date_format: u8,
+    // 0 for 24 hour times, 1 for 12 hour ones
+    #[serde(default)]
+    pub time_format: u8,
+    #[serde(default)]
+    pub lang: String,
+    #[serde(default)]
+    pub daily_goal: u32,
+    #[serde(default)]
+    pub weekly_goal: u32,
+    #[serde(default)]
+    pub karma: f64,
+    #[serde(default)]
+    pub karma_trend: String,
+    #[serde(default)]
+    pub is_premium: bool,
+    pub premium_until: Option<String>,
+    pub join_date: Option<String>,
+}
+
+fn monday() -> u8 {
+    1
+}
+
+impl User {
+    // Offset of the timezone of the user from UTC in seconds, taken from
+    // its GMT string, like -03:30
+    pub fn utc_offset(&self) -> i64 {
+        let gmt = self.tz_info.gmt_string.as_str();
+        let sign = match gmt.starts_with('-') {
+            true => -1,
+            false => 1,
+        };
+        let mut parts = gmt.trim_start_matches(|c| c == '+' || c == '-').split(':');
+        match (
+            parts.next().and_then(|h| h.parse::<i64>().ok()),
+            parts.next().and_then(|m| m.parse::<i64>().ok()),
+        ) {
+            (Some(hours), Some(minutes)) => sign * (hours * 3600 + minutes * 60),
+            _ => (self.tz_info.hours * 3600 + self.tz_info.minutes * 60) as i64,
+        }
+    }
+
+    // Days and times as the user chose to see them, in strftime syntax
+    pub fn day_format(&self) -> &'static str {
+        match self.date_format {
+            1 => "%m-%d-%Y",
+            _ => "%d-%m-%Y",
+        }
+    }
+
+    pub fn datetime_format(&self) -> String {
+        let time = match self.time_format {
+            1 => "%I:%M %p",
+            _ => "%H:%M",
+        };
+        format!("{} {}", self.day_format(), time)
+    }
+}
+
+impl Resource for User {
+    fn resource(&self) -> String {
+        String::from("user")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimezoneInfo {
+    pub timezone: String,
+    pub gmt_string: String,
+    pub hours: i32,
+    pub minutes: i32,
+    #[serde(default)]
+    pub is_dst: i32,
+}
+
+// Notification settings, synced as their own resource
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSettings {
+    #[serde(default)]
+    pub reminder_push: bool,
+    #[serde(default)]
+    pub reminder_desktop: bool,
+    #[serde(default)]
+    pub reminder_email: bool,
+    #[serde(default)]
+    pub completed_sound_desktop: bool,
+    #[serde(default)]
+    pub completed_sound_mobile: bool,
+}
+
+impl Resource for UserSettings {
+    fn resource(&self) -> String {
+        String::from("user_settings")
+    }
+}
@@ -0,0 +1,91 @@
+use hmac::{Hmac, Mac, NewMac};
+use serde::{self, Deserialize, Serialize};
+use serde_json::{self, Value};
+use sha2::Sha256;
+
+use crate::api::resource::{Item, Note, Project};
+
+// Header holding the base64 encoded HMAC-SHA256 of the body, keyed with the
+// client secret of the app the webhook was registered for
+pub const SIGNATURE_HEADER: &str = "X-Todoist-Hmac-SHA256";
+
+// A request Todoist sends to a webhook, like item:completed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event_name: String,
+    pub user_id: u32,
+    pub event_data: Value,
+    pub initiator: Option<Value>,
+    pub version: Option<String>,
+}
+
+pub enum EventData {
+    Item(Item),
+    Note(Note),
+    Project(Project),
+    Other(Value),
+}
+
+impl WebhookEvent {
+    // Events are named after the resource they are about, like note:added
+    pub fn data(&self) -> Result<EventData, serde_json::Error> {
+        let data = self.event_data.clone();
+        match self.event_name.split(':').next() {
+            Some("item") => Ok(EventData::Item(serde_json::from_value(data)?)),
+            Some("note") => Ok(EventData::Note(serde_json::from_value(data)?)),
+            Some("project") => Ok(EventData::Project(serde_json::from_value(data)?)),
+            _ => Ok(EventData::Other(data)),
+        }
+    }
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body);
+    base64::encode(mac.finalize().into_bytes())
+}
+
+// Signatures are compared in constant time
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    let signature = match base64::decode(signature.trim()) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body);
+    mac.verify(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_signed_bodies() {
+        let body = br#"{"event_name":"item:added"}"#;
+        let signature = sign("secret", body);
+        assert!(verify("secret", body, &signature));
+        assert!(verify("secret", body, &format!(" {}\n", signature)));
+        assert!(!verify("other secret", body, &signature));
+    }
+
+    #[test]
+    fn rejects_tampered_bodies() {
+        let signature = sign("secret", br#"{"event_name":"item:added"}"#);
+        assert!(!verify(
+            "secret",
+            br#"{"event_name":"item:deleted"}"#,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        let body = b"{}";
+        assert!(!verify("secret", body, "not base64!"));
+        assert!(!verify("secret", body, ""));
+        assert!(!verify("secret", body, &base64::encode(b"short")));
+    }
+}
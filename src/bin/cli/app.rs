@@ -0,0 +1,1008 @@
+use std::convert::TryFrom;
+
+use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
+
+use dothis::api::resource::Color;
+
+use super::command::duration_of;
+use super::config::CONFIG_KEYS;
+
+// Completed tasks can only be listed
+const LIST_RESOURCES: [&str; 5] = ["tasks", "projects", "labels", "notes", "completed"];
+const EVENT_TYPES: [&str; 9] = [
+    "added",
+    "updated",
+    "deleted",
+    "completed",
+    "uncompleted",
+    "archived",
+    "unarchived",
+    "shared",
+    "left",
+];
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+pub fn build_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("dothis")
+        .version("1.0")
+        .author("Tomas Farias")
+        .about("dothis the CLI for Todoist")
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(
+            Arg::with_name("token")
+                .takes_value(true)
+                .require_equals(true)
+                .long("token")
+                .short("t")
+                .global(true)
+                .env("TODOIST_API_TOKEN")
+                .hide_env_values(true)
+                .help("Todoist API token, not required if TODOIST_API_TOKEN environment variable is set, the profile has a token or after running login")
+        )
+        .arg(
+            Arg::with_name("profile")
+                .takes_value(true)
+                .long("profile")
+                .short("p")
+                .global(true)
+                .env("DOTHIS_PROFILE")
+                .help("configuration profile to use, defaults to the configured default_profile")
+        )
+        .arg(
+            Arg::with_name("dry-run")
+                .long("dry-run")
+                .global(true)
+                .help("print the commands that would be sent and what they would change, without sending them")
+        )
+        .subcommand(list_subcommand())
+        .subcommand(add_subcommand())
+        .subcommand(edit_subcommand())
+        .subcommand(
+            SubCommand::with_name("mv")
+                .about("move a task to a project, a section or under another task")
+                .arg(picked_arg(task_arg()))
+                .arg(project_arg().help("move the task to this project"))
+                .arg(section_arg().help("move the task to this section"))
+                .arg(parent_arg("move the task under this task"))
+                .group(
+                    ArgGroup::with_name("destination")
+                        .args(&["project", "section", "parent"])
+                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("done")
+                .about("complete tasks, recurring tasks are moved to their next due date")
+                .arg(tasks_arg())
+        )
+        .subcommand(
+            SubCommand::with_name("reschedule")
+                .about("change the due date of tasks")
+                .arg(tasks_arg())
+                .arg(due_arg().required(true))
+        )
+        .subcommand(
+            SubCommand::with_name("assign")
+                .about("make a collaborator of the shared project of a task responsible for it")
+                .arg(Arg::with_name("task").required(true).help("task id or content"))
+                .arg(
+                    Arg::with_name("assignee")
+                        .required_unless("unassign")
+                        .help("collaborator email or name, or me")
+                )
+                .arg(
+                    Arg::with_name("unassign")
+                        .long("unassign")
+                        .conflicts_with("assignee")
+                        .help("make nobody responsible for the task")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("share")
+                .about("invite users to a project by email, they become collaborators once they accept")
+                .arg(project_id_arg())
+                .arg(
+                    Arg::with_name("email")
+                        .required(true)
+                        .multiple(true)
+                        .help("emails of the users to invite")
+                )
+                .arg(
+                    Arg::with_name("remove")
+                        .long("remove")
+                        .help("remove the users from the project instead")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("import-lines")
+                .about("add a task for every line read from stdin")
+                .arg(project_arg().help("project to add the tasks to, defaults to the profile default_project"))
+                .arg(section_arg())
+                .arg(label_arg())
+                .arg(priority_arg())
+                .arg(due_arg())
+        )
+        .subcommand(
+            SubCommand::with_name("rm")
+                .about("delete a resource")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("task").arg(task_arg()).arg(yes_arg()))
+                .subcommand(
+                    SubCommand::with_name("project")
+                        .about("delete a project with all its sections, tasks and subprojects")
+                        .arg(project_id_arg())
+                        .arg(yes_arg())
+                )
+                .subcommand(SubCommand::with_name("label").arg(label_id_arg()).arg(yes_arg()))
+                .subcommand(SubCommand::with_name("note").arg(id_arg("note id")))
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("show the details of a task or project")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("task").arg(picked_arg(task_arg())))
+                .subcommand(SubCommand::with_name("project").arg(picked_arg(project_id_arg())))
+        )
+        .subcommand(
+            SubCommand::with_name("undo")
+                .about("revert the last write commands")
+                .arg(
+                    Arg::with_name("steps")
+                        .long("steps")
+                        .short("n")
+                        .takes_value(true)
+                        .validator(is_number)
+                        .default_value("1")
+                        .help("number of commands to revert, see history")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("list the write commands that can be undone, most recent first")
+        )
+        .subcommand(export_subcommand())
+        .subcommand(import_subcommand())
+        .subcommand(template_subcommand())
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("show karma, goals, streaks and charts of the tasks completed in the last days and weeks")
+        )
+        .subcommand(
+            SubCommand::with_name("watch")
+                .about("poll for changes to tasks and print those added, updated, completed, uncompleted or deleted")
+                .arg(query_arg())
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .validator(is_interval)
+                        .default_value("30s")
+                        .help("time between polls, like 30s, 5m or 1h")
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print events as JSON lines, with the task as the API returns it")
+                )
+                .arg(
+                    Arg::with_name("exec")
+                        .long("exec")
+                        .takes_value(true)
+                        .help("shell command to run for every event, with the task as JSON on stdin and the event in DOTHIS_EVENT")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("daemon")
+                .about("serve tasks to editor plugins over JSON-RPC, from a cache kept up to date in the background")
+                .long_about("Serves the list, add, update, complete and sync methods over JSON-RPC 2.0, on a Unix socket \
+                             with one request per line, or over HTTP on a local port. Reads are answered from the cache, \
+                             writes of a batch are sent in a single request.")
+                .arg(
+                    Arg::with_name("socket")
+                        .long("socket")
+                        .takes_value(true)
+                        .help("path of the Unix socket to listen on, only accessible to the current user")
+                )
+                .arg(
+                    Arg::with_name("port")
+                        .long("port")
+                        .takes_value(true)
+                        .validator(is_number)
+                        .help("port to listen on for HTTP requests instead, authenticated with a secret written to the data directory")
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .default_value("127.0.0.1")
+                        .help("loopback address to listen on for HTTP requests")
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .validator(is_interval)
+                        .default_value("1m")
+                        .help("time between syncs of the cache, like 30s, 5m or 1h")
+                )
+                .group(
+                    ArgGroup::with_name("listen")
+                        .args(&["socket", "port"])
+                        .required(true)
+                )
+        )
+        .subcommand(git_subcommand())
+        .subcommand(webhook_subcommand())
+        .subcommand(notifications_subcommand())
+        .subcommand(
+            SubCommand::with_name("log")
+                .about("list events of the activity log, oldest first, defaults to those of the current week")
+                .arg(
+                    Arg::with_name("object-type")
+                        .long("object-type")
+                        .takes_value(true)
+                        .possible_values(&["item", "note", "project"])
+                        .help("only list events of tasks, comments or projects")
+                )
+                .arg(
+                    Arg::with_name("event")
+                        .long("event")
+                        .takes_value(true)
+                        .possible_values(&EVENT_TYPES)
+                        .help("only list events of this type")
+                )
+                .arg(project_arg().help("only list events of tasks and comments of this project"))
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .takes_value(true)
+                        .validator(is_date)
+                        .help("list events since this day or time, like 2020-01-31 or 2020-01-31T09:00")
+                )
+                .arg(
+                    Arg::with_name("follow")
+                        .long("follow")
+                        .short("f")
+                        .help("keep polling the activity log, printing new events as they happen")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("backup")
+                .about("write every resource of the account, with archived projects and completed tasks, to an archive")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                        .help("archive to write, - writes to stdout, defaults to a timestamped file in the data directory")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("restore")
+                .about("add back projects of an archive written by backup, archived ones too, with their sections, tasks, completed tasks and notes")
+                .arg(
+                    Arg::with_name("archive")
+                        .required(true)
+                        .help("archive to restore from")
+                )
+                .arg(
+                    project_arg()
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("project to restore, by name or path in the archive, defaults to every project")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("serve-ical")
+                .about("serve the iCalendar feed of export ical over HTTP, for calendar clients to subscribe to")
+                .arg(
+                    Arg::with_name("port")
+                        .long("port")
+                        .takes_value(true)
+                        .validator(is_number)
+                        .default_value("8080")
+                        .help("port to listen on")
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .default_value("127.0.0.1")
+                        .help("address to listen on, the feed is not authenticated")
+                )
+                .args(&export_args())
+                .arg(events_arg())
+        )
+        .subcommand(config_subcommand())
+        .subcommand(
+            SubCommand::with_name("login")
+                .about("store the API token of the selected profile in a file only readable by the current user")
+                .long_about("Reads the Todoist API token from stdin and stores it in a file only readable by the current user. \
+                             Prefer this or a profile token_command over --token, which leaks into shell history and process listings.")
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("print a shell completion script")
+                .arg(Arg::with_name("shell").required(true).possible_values(&SHELLS))
+        )
+        .subcommand(
+            // Used by completion scripts to complete resource names from the cache
+            SubCommand::with_name("_complete")
+                .setting(AppSettings::Hidden)
+                .arg(Arg::with_name("resource").required(true).possible_values(&["projects", "labels"]))
+        )
+}
+
+fn webhook_subcommand<'a, 'b>() -> App<'a, 'b> {
+    let secret_arg = || {
+        Arg::with_name("secret")
+            .long("secret")
+            .takes_value(true)
+            .env("DOTHIS_WEBHOOK_SECRET")
+            .hide_env_values(true)
+            .help("client secret of the Todoist app, defaults to the profile webhook_secret")
+    };
+    SubCommand::with_name("webhook")
+        .about("receive Todoist webhooks")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("receive webhook requests, verify their signature, update the cache and run the profile hooks")
+                .arg(
+                    Arg::with_name("port")
+                        .long("port")
+                        .takes_value(true)
+                        .validator(is_number)
+                        .default_value("8080")
+                        .help("port to listen on"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .default_value("127.0.0.1")
+                        .help("address to listen on"),
+                )
+                .arg(secret_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("sign")
+                .about("print the signature of a payload, to send signed sample requests to serve")
+                .arg(secret_arg())
+                .arg(Arg::with_name("file").help("payload to sign, read from stdin if not given")),
+        )
+}
+
+fn git_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("git")
+        .about("link git repositories to tasks")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("hook")
+                .about("manage the git hooks of the current repository")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("install")
+                        .about("install a post-commit hook completing the tasks commits reference, like \"closes todoist:123\" or \"#task-123\"")
+                        .arg(
+                            Arg::with_name("force")
+                                .long("force")
+                                .help("replace an existing post-commit hook")
+                        )
+                )
+        )
+        .subcommand(
+            // Run by the post-commit hook
+            SubCommand::with_name("post-commit").setting(AppSettings::Hidden)
+        )
+        .subcommand(
+            SubCommand::with_name("branch")
+                .about("create a branch named after the content of a task and switch to it")
+                .arg(Arg::with_name("task").required(true).help("task id or content"))
+        )
+        .subcommand(
+            SubCommand::with_name("todo-scan")
+                .about("add a task for every TODO: comment of the files tracked by git, skipping those that have one")
+                .arg(project_arg().help("project of the tasks, defaults to the profile todo_project"))
+                .arg(Arg::with_name("path").multiple(true).help("only scan these files or directories"))
+        )
+}
+
+fn notifications_subcommand<'a, 'b>() -> App<'a, 'b> {
+    let id_arg = || {
+        Arg::with_name("id")
+            .required(true)
+            .validator(is_number)
+            .help("notification id, as listed")
+    };
+    SubCommand::with_name("notifications")
+        .about("list notifications of collaborators and share invitations, marking those new since the last listing")
+        .arg(
+            Arg::with_name("unread")
+                .long("unread")
+                .help("only list unread notifications"),
+        )
+        .subcommand(
+            SubCommand::with_name("read")
+                .about("mark notifications as read")
+                .arg(
+                    Arg::with_name("id")
+                        .multiple(true)
+                        .required_unless("all")
+                        .validator(is_number)
+                        .help("notification ids, as listed"),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .conflicts_with("id")
+                        .help("mark every notification as read"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("accept")
+                .about("accept the share invitation of a notification")
+                .arg(id_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("reject")
+                .about("reject the share invitation of a notification")
+                .arg(id_arg()),
+        )
+}
+
+fn list_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("list")
+        .about("list resources, defaults to the profile default_view")
+        .arg(
+            Arg::with_name("resource")
+                .possible_values(&LIST_RESOURCES)
+                .help("resource to list, completed lists completed tasks"),
+        )
+        .arg(project_arg().help("only list tasks of this project"))
+        .arg(query_arg())
+        .arg(
+            Arg::with_name("filter")
+                .long("filter")
+                .short("f")
+                .takes_value(true)
+                .conflicts_with("query")
+                .help("only tasks matching the query of this saved filter, by id or name"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .takes_value(true)
+                .possible_values(&["table", "ids"])
+                .default_value("table")
+                .help("print a table, or only ids to pipe into commands like done"),
+        )
+        .arg(
+            Arg::with_name("assignee")
+                .long("assignee")
+                .takes_value(true)
+                .help("only list tasks of shared projects assigned to this collaborator, by email or name, or me"),
+        )
+        .arg(
+            Arg::with_name("since")
+                .long("since")
+                .takes_value(true)
+                .validator(is_date)
+                .help("only list tasks completed since this day or time, like 2020-01-31 or 2020-01-31T09:00"),
+        )
+        .arg(
+            Arg::with_name("until")
+                .long("until")
+                .takes_value(true)
+                .validator(is_date)
+                .help("only list tasks completed until this day or time"),
+        )
+        .arg(
+            Arg::with_name("description")
+                .long("description")
+                .help("add a column with the first line of task descriptions"),
+        )
+}
+
+fn add_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("add")
+        .about("add a resource")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("task")
+                .arg(
+                    Arg::with_name("content")
+                        .required_unless("editor")
+                        .help("task content"),
+                )
+                .arg(
+                    project_arg().help(
+                        "project to add the task to, defaults to the profile default_project",
+                    ),
+                )
+                .arg(section_arg())
+                .arg(parent_arg("parent task id or content"))
+                .arg(priority_arg())
+                .arg(due_arg())
+                .arg(label_arg())
+                .arg(order_arg())
+                .args(&description_args())
+                .arg(editor_arg(
+                    "write the task in $VISUAL or $EDITOR, starting from the arguments given",
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("project")
+                .arg(Arg::with_name("name").required(true).help("project name"))
+                .arg(color_arg())
+                .arg(parent_arg("parent project id, name or path"))
+                .arg(order_arg())
+                .arg(
+                    Arg::with_name("favorite")
+                        .long("favorite")
+                        .help("mark project as favorite"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("label")
+                .arg(Arg::with_name("name").required(true).help("label name"))
+                .arg(color_arg())
+                .arg(order_arg())
+                .arg(
+                    Arg::with_name("favorite")
+                        .long("favorite")
+                        .help("mark label as favorite"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("note")
+                .about("add a comment to a task")
+                .arg(task_arg())
+                .arg(
+                    Arg::with_name("content")
+                        .required(true)
+                        .help("note content"),
+                ),
+        )
+}
+
+fn edit_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("edit")
+        .about("edit a resource")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("task")
+                .arg(picked_arg(task_arg()))
+                .arg(
+                    Arg::with_name("content")
+                        .long("content")
+                        .takes_value(true)
+                        .help("new task content"),
+                )
+                .arg(priority_arg())
+                .arg(due_arg())
+                .arg(label_arg().help("replace the task labels"))
+                .arg(
+                    project_arg()
+                        .help("move the task to this project")
+                        .conflicts_with_all(&["section", "parent"]),
+                )
+                .arg(
+                    section_arg()
+                        .help("move the task to this section")
+                        .conflicts_with("parent"),
+                )
+                .arg(parent_arg("move the task under this task"))
+                .args(&description_args())
+                // Conflicts with members of the group make them conflict with each
+                // other, they are checked when editing instead
+                .arg(editor_arg(
+                    "edit the task and add a comment in $VISUAL or $EDITOR",
+                ))
+                .group(changes_group(&[
+                    "content",
+                    "description",
+                    "description-file",
+                    "priority",
+                    "due",
+                    "label",
+                    "project",
+                    "section",
+                    "parent",
+                    "editor",
+                ])),
+        )
+        .subcommand(
+            SubCommand::with_name("project")
+                .arg(picked_arg(project_id_arg()))
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("new project name"),
+                )
+                .arg(color_arg())
+                .args(&favorite_args())
+                .arg(parent_arg("move the project under this project"))
+                .group(changes_group(&[
+                    "name",
+                    "color",
+                    "favorite",
+                    "unfavorite",
+                    "parent",
+                ])),
+        )
+        .subcommand(
+            SubCommand::with_name("label")
+                .arg(label_id_arg())
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("new label name"),
+                )
+                .arg(color_arg())
+                .args(&favorite_args())
+                .group(changes_group(&["name", "color", "favorite", "unfavorite"])),
+        )
+        .subcommand(
+            SubCommand::with_name("note").arg(id_arg("note id")).arg(
+                Arg::with_name("content")
+                    .required(true)
+                    .help("new note content"),
+            ),
+        )
+}
+
+fn export_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("export")
+        .about("print tasks in a format read by other tools")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("ical")
+                .about("tasks with a due date as an iCalendar feed")
+                .args(&export_args())
+                .arg(events_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("markdown")
+                .about("the project tree as a Markdown document, with tasks as checkboxes")
+                .args(&document_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("org")
+                .about("the project tree as an Org-mode document, with tasks as checkboxes")
+                .args(&document_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("taskwarrior")
+                .about("tasks as the JSON of task export, with notes as annotations and subtasks as dependencies")
+                .args(&export_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("todotxt")
+                .about("tasks in the todo.txt format, with projects and labels as +project and @context")
+                .args(&export_args()),
+        )
+}
+
+fn import_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("import")
+        .about("add the tasks of a file written by another tool")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("taskwarrior")
+                .about("tasks as the JSON of task export, with annotations as notes and dependencies as subtasks")
+                .args(&import_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("todotxt")
+                .about("tasks in the todo.txt format, completed ones are added as completed")
+                .args(&import_args()),
+        )
+}
+
+fn import_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("file")
+            .required(true)
+            .help("file to import, - reads from stdin"),
+        project_arg().help(
+            "project to add the tasks to, defaults to their first existing project or the profile default_project",
+        ),
+    ]
+}
+
+// Exports select tasks like list does
+fn export_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        project_arg().help("only export tasks of this project"),
+        query_arg(),
+    ]
+}
+
+fn template_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("template")
+        .about("reuse project structures as Todoist CSV templates")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("print the sections, open tasks and notes of a project as a template")
+                .arg(
+                    Arg::with_name("project")
+                        .required(true)
+                        .help("project id, name or path, like Work/Backend"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("apply")
+                .about("add the sections, tasks and notes of a template to a project")
+                .arg(
+                    Arg::with_name("file")
+                        .required(true)
+                        .help("template to apply, - reads from stdin"),
+                )
+                .arg(
+                    Arg::with_name("into")
+                        .long("into")
+                        .takes_value(true)
+                        .required(true)
+                        .help("project id, name or path to add to"),
+                )
+                .arg(
+                    Arg::with_name("var")
+                        .long("var")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .validator(is_variable)
+                        .help("value of a {{name}} placeholder, as name=value"),
+                ),
+        )
+}
+
+// Documents have the whole tree under the project
+fn document_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        project_arg().help("only export this project and its subprojects"),
+        query_arg(),
+    ]
+}
+
+fn events_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("events")
+        .long("events")
+        .help("export events instead of to-dos, for calendar clients that do not show to-dos")
+}
+
+fn config_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("config")
+        .about("read and write the configuration of the selected profile")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("get")
+                .about("print a configuration value")
+                .arg(
+                    Arg::with_name("key")
+                        .required(true)
+                        .possible_values(&CONFIG_KEYS),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set")
+                .about("set a configuration value, an empty value unsets it")
+                .arg(
+                    Arg::with_name("key")
+                        .required(true)
+                        .possible_values(&CONFIG_KEYS),
+                )
+                .arg(Arg::with_name("value").required(true)),
+        )
+}
+
+fn query_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("query")
+        .long("query")
+        .short("q")
+        .takes_value(true)
+        .help("only tasks matching terms joined with &, like \"overdue & !@waiting\": overdue, today, no date, p1 to p4, @label, #project or search: text, in the content or description")
+}
+
+fn editor_arg<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("editor")
+        .long("editor")
+        .short("e")
+        .help(help)
+}
+
+// Edits have to change at least one of the given arguments
+fn changes_group<'a>(args: &[&'a str]) -> ArgGroup<'a> {
+    ArgGroup::with_name("changes")
+        .args(args)
+        .multiple(true)
+        .required(true)
+}
+
+fn id_arg<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("id")
+        .required(true)
+        .validator(is_id)
+        .help(help)
+}
+
+fn task_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("task")
+        .required(true)
+        .help("task id or content")
+}
+
+// Bulk commands take any number of tasks, or read them from stdin
+fn tasks_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("task").multiple(true).help(
+        "task ids or contents, picked in a terminal or read from stdin one per line if not given",
+    )
+}
+
+// Resources left out are picked from the cache in a terminal
+fn picked_arg<'a, 'b>(arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
+    arg.required(false)
+}
+
+// Deletes of resources matched by prefix or fuzzily are confirmed
+fn yes_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("yes")
+        .long("yes")
+        .short("y")
+        .help("delete without confirming names that only match approximately")
+}
+
+fn project_id_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("project")
+        .required(true)
+        .help("project id, name or path, like Work/Backend")
+}
+
+fn label_id_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("label")
+        .required(true)
+        .help("label id or name")
+}
+
+fn project_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("project")
+        .long("project")
+        .short("P")
+        .takes_value(true)
+        .help("project id, name or path, like Work/Backend")
+}
+
+fn section_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("section")
+        .long("section")
+        .short("s")
+        .takes_value(true)
+        .help("section id, name or path, like Work/Backend/Review")
+}
+
+fn label_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("label")
+        .long("label")
+        .short("l")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("label id or name, can be given more than once")
+}
+
+fn parent_arg<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("parent")
+        .long("parent")
+        .takes_value(true)
+        .help(help)
+}
+
+fn priority_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("priority")
+        .long("priority")
+        .short("r")
+        .takes_value(true)
+        .possible_values(&["1", "2", "3", "4"])
+        .help("priority from 1 (normal) to 4 (urgent)")
+}
+
+fn due_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("due")
+        .long("due")
+        .short("d")
+        .takes_value(true)
+        .help("due date in natural language, like \"tomorrow\" or \"every monday\"")
+}
+
+fn order_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("order")
+        .long("order")
+        .takes_value(true)
+        .validator(is_number)
+        .help("position among siblings")
+}
+
+fn color_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("color")
+        .long("color")
+        .short("c")
+        .takes_value(true)
+        .validator(is_color)
+        .help("color name, like \"berry red\", or hex code")
+}
+
+// Both are changes when editing, which cannot conflict with each other
+fn description_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("description")
+            .long("description")
+            .takes_value(true)
+            .help("task description"),
+        Arg::with_name("description-file")
+            .long("description-file")
+            .takes_value(true)
+            .help("read the task description from this file, - for stdin"),
+    ]
+}
+
+fn favorite_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("favorite")
+            .long("favorite")
+            .help("mark as favorite"),
+        Arg::with_name("unfavorite")
+            .long("unfavorite")
+            .conflicts_with("favorite")
+            .help("unmark as favorite"),
+    ]
+}
+
+fn is_id(value: String) -> Result<(), String> {
+    match value.parse::<u32>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("{} is not a valid id", value)),
+    }
+}
+
+fn is_number(value: String) -> Result<(), String> {
+    match value.parse::<u32>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("{} is not a positive number", value)),
+    }
+}
+
+fn is_interval(value: String) -> Result<(), String> {
+    match duration_of(&value) {
+        Some(_) => Ok(()),
+        None => Err(format!("{} is not an interval like 30s, 5m or 1h", value)),
+    }
+}
+
+fn is_date(value: String) -> Result<(), String> {
+    match time::strptime(&value, "%Y-%m-%dT%H:%M").or_else(|_| time::strptime(&value, "%Y-%m-%d")) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!(
+            "{} is not a date like 2020-01-31 or 2020-01-31T09:00",
+            value
+        )),
+    }
+}
+
+fn is_variable(value: String) -> Result<(), String> {
+    match value.find('=') {
+        Some(index) if index > 0 => Ok(()),
+        _ => Err(format!("{} is not a name=value pair", value)),
+    }
+}
+
+fn is_color(value: String) -> Result<(), String> {
+    match Color::try_from(value.as_str()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("{} is not a Todoist color", value)),
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use dothis::api::client::{ProjectData, TodoistResponse};
+use dothis::api::resource::{CompletedItem, Project};
+
+use super::error::DothisError;
+
+// Archives written by later versions may hold resources this one would lose
+// when restoring, so they are refused
+pub const ARCHIVE_VERSION: u32 = 1;
+
+// A full sync of every resource type, along with the archived projects, their
+// sections, tasks and comments, and the completed tasks the sync endpoint
+// leaves out
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    pub date: String,
+    pub profile: String,
+    pub resources: TodoistResponse,
+    #[serde(default)]
+    pub archived_projects: Vec<Project>,
+    #[serde(default)]
+    pub archived_data: Vec<ProjectData>,
+    #[serde(default)]
+    pub completed: Vec<CompletedItem>,
+}
+
+impl Archive {
+    pub fn new(
+        profile: &str,
+        resources: TodoistResponse,
+        archived_projects: Vec<Project>,
+        archived_data: Vec<ProjectData>,
+        completed: Vec<CompletedItem>,
+    ) -> Archive {
+        Archive {
+            version: ARCHIVE_VERSION,
+            date: time::strftime("%Y-%m-%dT%H:%M:%SZ", &time::now_utc()).unwrap_or_default(),
+            profile: profile.to_string(),
+            resources: resources,
+            archived_projects: archived_projects,
+            archived_data: archived_data,
+            completed: completed,
+        }
+    }
+
+    // Archives are kept per profile, named after the time they were taken
+    pub fn default_path(profile: &str) -> Result<PathBuf, DothisError> {
+        let stamp = time::strftime("%Y%m%dT%H%M%SZ", &time::now_utc()).unwrap_or_default();
+        match dirs::data_dir() {
+            Some(dir) => Ok(dir
+                .join("dothis")
+                .join("backups")
+                .join(profile)
+                .join(format!("{}.json", stamp))),
+            None => Err(DothisError::MissingConfigDir),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Archive, DothisError> {
+        let archive: Archive = serde_json::from_str(&fs::read_to_string(path)?)?;
+        match archive.version > ARCHIVE_VERSION {
+            true => Err(DothisError::UnsupportedArchive(archive.version)),
+            false => Ok(archive),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DothisError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
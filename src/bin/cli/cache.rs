@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use dothis::api::client::{TodoistClient, TodoistResponse};
+use dothis::api::resolve::{Match, ResolveError, Resolver};
+use dothis::api::resource::{
+    Collaborator, CollaboratorState, Filter, Item, Label, LiveNotification, MemberState, Note,
+    Project, Section, User,
+};
+
+use super::error::DothisError;
+
+// Local copy of the last resources fetched for a profile. It is used where
+// going to the API would be too slow, like shell completions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    #[serde(skip)]
+    path: PathBuf,
+    // Whether resources were fetched again to resolve a name
+    #[serde(skip)]
+    refreshed: bool,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    #[serde(default)]
+    pub items: Vec<Item>,
+    #[serde(default)]
+    pub notes: Vec<Note>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+    #[serde(default)]
+    pub collaborators: Vec<Collaborator>,
+    #[serde(default)]
+    pub collaborator_states: Vec<CollaboratorState>,
+    #[serde(default)]
+    pub user: Option<User>,
+    #[serde(default)]
+    pub live_notifications: Vec<LiveNotification>,
+    // Highest seq_no of the notifications listed so far, later ones are new
+    #[serde(default)]
+    pub notifications_seen: u64,
+}
+
+impl Cache {
+    fn path(profile: &str) -> Result<PathBuf, DothisError> {
+        match dirs::cache_dir() {
+            Some(dir) => Ok(dir.join("dothis").join(format!("{}.json", profile))),
+            None => Err(DothisError::MissingConfigDir),
+        }
+    }
+
+    // A missing or outdated cache is not an error, it is replaced on the next save
+    pub fn load(profile: &str) -> Result<Cache, DothisError> {
+        let path = Cache::path(profile)?;
+        let mut cache = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+            Err(_) => Cache::default(),
+        };
+        cache.path = path;
+        Ok(cache)
+    }
+
+    pub fn save(&self) -> Result<(), DothisError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    // Responses are full syncs of the resource types requested, so every
+    // resource type present replaces what was cached
+    pub fn update(&mut self, response: TodoistResponse) {
+        if let Some(projects) = response.projects {
+            self.projects = projects;
+        }
+        if let Some(sections) = response.sections {
+            self.sections = sections;
+        }
+        if let Some(items) = response.items {
+            self.items = items;
+        }
+        if let Some(notes) = response.notes {
+            self.notes = notes;
+        }
+        if let Some(labels) = response.labels {
+            self.labels = labels;
+        }
+        if let Some(filters) = response.filters {
+            self.filters = filters;
+        }
+        if let Some(collaborators) = response.collaborators {
+            self.collaborators = collaborators;
+        }
+        if let Some(states) = response.collaborator_states {
+            self.collaborator_states = states;
+        }
+        if response.user.is_some() {
+            self.user = response.user;
+        }
+        if let Some(notifications) = response.live_notifications {
+            self.live_notifications = notifications;
+        }
+    }
+
+    // Incremental responses only hold what changed since their sync_token,
+    // which replaces the cached resources with the same id, or removes them
+    // once deleted. Completed tasks are removed too, as full syncs leave
+    // them out.
+    pub fn merge(&mut self, response: TodoistResponse) {
+        if response.full_sync {
+            return self.update(response);
+        }
+        merge(
+            &mut self.projects,
+            response.projects,
+            |p| p.id,
+            |p| p.is_deleted,
+        );
+        merge(
+            &mut self.sections,
+            response.sections,
+            |s| s.id,
+            |s| s.is_deleted,
+        );
+        merge(
+            &mut self.items,
+            response.items,
+            |i| i.id,
+            |i| i.is_deleted || i.checked,
+        );
+        merge(&mut self.notes, response.notes, |n| n.id, |n| n.is_deleted);
+        merge(
+            &mut self.labels,
+            response.labels,
+            |l| l.id,
+            |l| l.is_deleted,
+        );
+        merge(
+            &mut self.filters,
+            response.filters,
+            |f| f.id,
+            |f| f.is_deleted,
+        );
+        merge(
+            &mut self.collaborators,
+            response.collaborators,
+            |c| c.id,
+            |_| false,
+        );
+        merge(
+            &mut self.collaborator_states,
+            response.collaborator_states,
+            |s| (s.project_id, s.user_id),
+            |s| s.is_deleted,
+        );
+        merge(
+            &mut self.live_notifications,
+            response.live_notifications,
+            |n| n.id,
+            |n| n.is_deleted,
+        );
+        if response.user.is_some() {
+            self.user = response.user;
+        }
+    }
+
+    pub fn resolver(&self) -> Resolver {
+        Resolver::new(
+            &self.projects,
+            &self.sections,
+            &self.labels,
+            &self.filters,
+            &self.items,
+            &self.collaborators,
+        )
+    }
+
+    // Resolves names against the cached resources first, fetching them again
+    // once if nothing matched, as the cache may be outdated
+    pub fn resolve(
+        &mut self,
+        client: &TodoistClient,
+        resource_type: &str,
+        query: &str,
+    ) -> Result<u32, DothisError> {
+        Ok(self.lookup(client, resource_type, query)?.id)
+    }
+
+    // Like resolve, telling whether the query named the resource exactly
+    pub fn lookup(
+        &mut self,
+        client: &TodoistClient,
+        resource_type: &str,
+        query: &str,
+    ) -> Result<Match, DothisError> {
+        match self.resolver().lookup(resource_type, query) {
+            Err(ResolveError::NotFound(_, _)) if !self.refreshed => {
+                self.refresh(client)?;
+                self.lookup(client, resource_type, query)
+            }
+            result => Ok(result?),
+        }
+    }
+
+    pub fn refresh(&mut self, client: &TodoistClient) -> Result<(), DothisError> {
+        let response = client.get_resources(vec![
+            "projects",
+            "sections",
+            "labels",
+            "filters",
+            "items",
+            "notes",
+            "collaborators",
+            "user",
+        ])?;
+        self.update(response);
+        self.refreshed = true;
+        self.save()
+    }
+
+    // Snapshots for undo are taken from the cache, so resources missing from
+    // it are fetched first
+    pub fn ensure(
+        &mut self,
+        client: &TodoistClient,
+        resource_type: &str,
+        ids: &[u32],
+    ) -> Result<(), DothisError> {
+        let missing = ids.iter().any(|id| match resource_type {
+            "tasks" => self.item(*id).is_none(),
+            "projects" => self.project(*id).is_none(),
+            "labels" => self.label(*id).is_none(),
+            "notes" => self.note(*id).is_none(),
+            _ => false,
+        });
+        match missing && !self.refreshed {
+            true => self.refresh(client),
+            false => Ok(()),
+        }
+    }
+
+    // How resources are referred to in messages, falling back to their id
+    pub fn name(&self, resource_type: &str, id: u32) -> String {
+        let name = match resource_type {
+            "tasks" => self.item(id).map(|i| i.content.clone()),
+            "projects" => self.project(id).map(|p| p.name.clone()),
+            "labels" => self.label(id).map(|l| l.name.clone()),
+            _ => None,
+        };
+        name.unwrap_or(id.to_string())
+    }
+
+    pub fn project(&self, id: u32) -> Option<&Project> {
+        self.projects.iter().find(|p| p.id == id)
+    }
+
+    pub fn section(&self, id: u32) -> Option<&Section> {
+        self.sections.iter().find(|s| s.id == id)
+    }
+
+    pub fn item(&self, id: u32) -> Option<&Item> {
+        self.items.iter().find(|i| i.id == id)
+    }
+
+    pub fn label(&self, id: u32) -> Option<&Label> {
+        self.labels.iter().find(|l| l.id == id)
+    }
+
+    pub fn note(&self, id: u32) -> Option<&Note> {
+        self.notes.iter().find(|n| n.id == id)
+    }
+
+    pub fn collaborator(&self, id: u32) -> Option<&Collaborator> {
+        self.collaborators.iter().find(|c| c.id == id)
+    }
+
+    // Whether the user takes part in the shared project, and so can be
+    // assigned its tasks
+    pub fn is_member(&self, project_id: u32, user_id: u32) -> bool {
+        self.collaborator_states.iter().any(|s| {
+            s.project_id == project_id
+                && s.user_id == user_id
+                && s.state == MemberState::Active
+                && !s.is_deleted
+        })
+    }
+
+    // Users are referred to by name, the current one as "you"
+    pub fn user_name(&self, id: u32) -> String {
+        match (&self.user, self.collaborator(id)) {
+            (Some(user), _) if user.id == id => "you".to_string(),
+            (_, Some(collaborator)) => collaborator.full_name.clone(),
+            _ => format!("user {}", id),
+        }
+    }
+}
+
+fn merge<T, K, F, D>(cached: &mut Vec<T>, changes: Option<Vec<T>>, key: F, deleted: D)
+where
+    K: PartialEq,
+    F: Fn(&T) -> K,
+    D: Fn(&T) -> bool,
+{
+    for change in changes.into_iter().flatten() {
+        let position = cached.iter().position(|c| key(c) == key(&change));
+        match (position, deleted(&change)) {
+            (Some(position), true) => {
+                cached.remove(position);
+            }
+            (Some(position), false) => cached[position] = change,
+            (None, true) => {}
+            (None, false) => cached.push(change),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::convert::TryFrom;
+
+use clap::ArgMatches;
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{
+    AddItem, AddLabel, AddNote, AddProject, AddResource, Color, NewDueDate,
+};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::editor::{self, TaskDocument};
+use super::{check_response, description_of, id_of, resolve_arg, resolve_args, Command};
+
+pub struct AddCommand<'a> {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    resource_type: String,
+    args: ArgMatches<'a>,
+    default_project: Option<String>,
+}
+
+impl<'a> AddCommand<'a> {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        resource_type: &str,
+        args: ArgMatches<'a>,
+        default_project: Option<&str>,
+    ) -> AddCommand<'a> {
+        AddCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            resource_type: resource_type.to_string(),
+            args: args,
+            default_project: default_project.map(|p| p.to_string()),
+        }
+    }
+
+    fn resolve(&mut self, resource_type: &str, name: &str) -> Result<Option<u32>, DothisError> {
+        resolve_arg(
+            &self.client,
+            &mut self.cache,
+            &self.args,
+            resource_type,
+            name,
+        )
+    }
+
+    // Tasks without a project go to the inbox of the user, sent explicitly for
+    // the task to be listed under it, unless they are placed in a section or
+    // under a parent, whose project they go to
+    fn resolve_project(
+        &mut self,
+        query: Option<String>,
+        placed: bool,
+    ) -> Result<Option<u32>, DothisError> {
+        match query {
+            Some(query) => Ok(Some(self.cache.resolve(
+                &self.client,
+                "projects",
+                &query,
+            )?)),
+            None if placed => Ok(None),
+            None => {
+                if self.cache.user.is_none() {
+                    self.cache.refresh(&self.client)?;
+                }
+                Ok(self.cache.user.as_ref().and_then(|u| u.inbox_project))
+            }
+        }
+    }
+
+    // The profile default_project stands for a project not given, when the
+    // task is not placed in a section or under a parent
+    fn project_query(&self) -> Option<String> {
+        let placed = self.args.is_present("section") || self.args.is_present("parent");
+        self.args
+            .value_of("project")
+            .map(|p| p.to_string())
+            .or_else(|| self.default_project.clone().filter(|_| !placed))
+    }
+
+    // The task is written in an editor, starting from the arguments given
+    fn document_item(&mut self) -> Result<AddItem, DothisError> {
+        let document = TaskDocument {
+            content: self
+                .args
+                .value_of("content")
+                .unwrap_or_default()
+                .to_string(),
+            project: self.project_query().unwrap_or_default(),
+            section: self
+                .args
+                .value_of("section")
+                .unwrap_or_default()
+                .to_string(),
+            labels: self
+                .args
+                .values_of("label")
+                .map_or(Vec::new(), |l| l.map(|l| l.to_string()).collect()),
+            due: self.args.value_of("due").unwrap_or_default().to_string(),
+            priority: self
+                .args
+                .value_of("priority")
+                .map_or(1, |v| v.parse::<i32>().unwrap_or(1)),
+            description: description_of(&self.args)?.unwrap_or_default(),
+            ..TaskDocument::default()
+        };
+        let document = match editor::edit(&document, None)? {
+            Some(edited) => edited,
+            None if !document.content.is_empty() => document,
+            None => return Err(DothisError::EmptyContent),
+        };
+
+        let project = Some(document.project.clone()).filter(|p| !p.is_empty());
+        let section_id = document.section_id(&self.client, &mut self.cache)?;
+        let parent_id = self.resolve("tasks", "parent")?;
+        let placed = section_id.is_some() || parent_id.is_some();
+        Ok(AddItem {
+            project_id: self.resolve_project(project, placed)?,
+            section_id: section_id,
+            parent_id: parent_id,
+            labels: Some(document.label_ids(&self.client, &mut self.cache)?)
+                .filter(|l| !l.is_empty()),
+            content: document.content,
+            description: Some(document.description).filter(|d| !d.is_empty()),
+            due: Some(document.due)
+                .filter(|d| !d.is_empty())
+                .map(|d| NewDueDate::from_string(&d)),
+            priority: Some(document.priority),
+            child_order: self
+                .args
+                .value_of("order")
+                .map_or(None, |v| v.parse::<i32>().ok()),
+            day_order: None,
+            collapsed: None,
+            assigned_by_uid: None,
+            responsible_uid: None,
+            auto_reminder: None,
+            auto_parse_labels: None,
+        })
+    }
+
+    pub fn get_new_resource(&mut self) -> Result<AddResource, DothisError> {
+        match self.resource_type.as_str() {
+            "tasks" if self.args.is_present("editor") => {
+                Ok(AddResource::Item(self.document_item()?))
+            }
+            "tasks" => {
+                let section_id = self.resolve("sections", "section")?;
+                let parent_id = self.resolve("tasks", "parent")?;
+                let placed = section_id.is_some() || parent_id.is_some();
+                Ok(AddResource::Item(AddItem {
+                    project_id: self.resolve_project(self.project_query(), placed)?,
+                    section_id: section_id,
+                    parent_id: parent_id,
+                    labels: resolve_args(
+                        &self.client,
+                        &mut self.cache,
+                        &self.args,
+                        "labels",
+                        "label",
+                    )?,
+                    content: self
+                        .args
+                        .value_of("content")
+                        .expect("task content is required")
+                        .to_string(),
+                    description: description_of(&self.args)?,
+                    due: self.args.value_of("due").map(NewDueDate::from_string),
+                    priority: self
+                        .args
+                        .value_of("priority")
+                        .map_or(None, |v| v.parse::<i32>().ok()),
+                    child_order: self
+                        .args
+                        .value_of("order")
+                        .map_or(None, |v| v.parse::<i32>().ok()),
+                    day_order: None,
+                    collapsed: None,
+                    assigned_by_uid: None,
+                    responsible_uid: None,
+                    auto_reminder: None,
+                    auto_parse_labels: None,
+                }))
+            }
+            "projects" => {
+                let parent_id = self.resolve("projects", "parent")?;
+                Ok(AddResource::Project(AddProject::new(
+                    self.args
+                        .value_of("name")
+                        .expect("project name is required"),
+                    self.args.value_of("color"),
+                    parent_id,
+                    id_of(&self.args, "order"),
+                    self.favorite(),
+                )))
+            }
+            "labels" => Ok(AddResource::Label(AddLabel {
+                name: self
+                    .args
+                    .value_of("name")
+                    .expect("label name is required")
+                    .to_string(),
+                color: self
+                    .args
+                    .value_of("color")
+                    .map_or(None, |c| Color::try_from(c).ok()),
+                item_order: id_of(&self.args, "order"),
+                is_favorite: self.favorite(),
+            })),
+            "notes" => Ok(AddResource::Note(AddNote {
+                item_id: self.resolve("tasks", "task")?.expect("task is required"),
+                content: self
+                    .args
+                    .value_of("content")
+                    .expect("note content is required")
+                    .to_string(),
+                uids_to_notify: None,
+            })),
+            _ => return Err(DothisError::UnknownResource),
+        }
+    }
+
+    fn favorite(&self) -> Option<bool> {
+        match self.args.is_present("favorite") {
+            true => Some(true),
+            false => None,
+        }
+    }
+}
+
+impl<'a> Command for AddCommand<'a> {
+    // Prints the id of the new resource, so it can be used in scripts
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let new_resource = self.get_new_resource()?;
+        let temp_id = Uuid::new_v4();
+
+        let mut builder = self.client.builder();
+        builder.add(&new_resource, None, Some(temp_id));
+        let response = self.client.sync(&builder.build())?;
+        check_response(&response)?;
+
+        if let Some(id) = response.temp_id_mapping.get(&temp_id.to_string()) {
+            println!("{}", id);
+            let name = match &new_resource {
+                AddResource::Item(item) => item.content.as_str(),
+                _ => self
+                    .args
+                    .value_of("content")
+                    .or(self.args.value_of("name"))
+                    .unwrap_or_default(),
+            };
+            let description = format!("add {} {}", self.resource_type.trim_end_matches('s'), name);
+            self.journal
+                .record(&description, Inverse::delete(&self.resource_type, *id))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use serde_json::json;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::RawCommand;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{report_batch, Command};
+
+// Makes a collaborator of the shared project of a task responsible for it,
+// or nobody when no assignee is given
+pub struct AssignCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    task: u32,
+    assignee: Option<String>,
+}
+
+impl AssignCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        task: u32,
+        assignee: Option<&str>,
+    ) -> AssignCommand {
+        AssignCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            task: task,
+            assignee: assignee.map(|a| a.to_string()),
+        }
+    }
+
+    // Assignees are collaborators given by email or name, or me
+    fn resolve(&mut self, project_id: u32, query: &str) -> Result<u32, DothisError> {
+        if query != "me" {
+            return self.cache.resolve(&self.client, "collaborators", query);
+        }
+        if self.cache.user.is_none() {
+            self.cache.refresh(&self.client)?;
+        }
+        match &self.cache.user {
+            Some(user) => Ok(user.id),
+            None => Err(DothisError::NotCollaborator(
+                query.to_string(),
+                self.cache.name("projects", project_id),
+            )),
+        }
+    }
+}
+
+impl Command for AssignCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        self.cache.ensure(&self.client, "tasks", &[self.task])?;
+        let project_id = self.cache.item(self.task).map(|i| i.project_id);
+
+        let responsible = match (self.assignee.clone(), project_id) {
+            (Some(query), Some(project_id)) => {
+                let id = self.resolve(project_id, &query)?;
+                // Memberships change without the cache knowing
+                if !self.cache.is_member(project_id, id) {
+                    self.cache.refresh(&self.client)?;
+                }
+                if !self.cache.is_member(project_id, id) {
+                    return Err(DothisError::NotCollaborator(
+                        self.cache.user_name(id),
+                        self.cache.name("projects", project_id),
+                    ));
+                }
+                Some(id as i32)
+            }
+            // Tasks missing from the cache are trusted, like their ids
+            (Some(query), None) => Some(self.resolve(0, &query)? as i32),
+            (None, _) => None,
+        };
+        let undo = vec![Inverse::assign(&self.cache, self.task)];
+        let description = match responsible {
+            Some(id) => format!(
+                "assign {} to {}",
+                self.cache.name("tasks", self.task),
+                self.cache.user_name(id as u32)
+            ),
+            None => format!("unassign {}", self.cache.name("tasks", self.task)),
+        };
+
+        // Unassigning sends a null responsible_uid, which UpdateItem leaves out
+        let command = RawCommand::new(
+            "item_update",
+            json!({ "id": self.task, "responsible_uid": responsible }),
+        );
+        let results = self.client.sync_batch(&[command])?;
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&[self.task.to_string()], results)
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use dothis::api::client::TodoistClient;
+
+use super::super::archive::Archive;
+use super::super::error::DothisError;
+use super::Command;
+
+// Writes every resource of the account to an archive, printing where
+pub struct BackupCommand {
+    client: TodoistClient,
+    profile: String,
+    output: Option<String>,
+}
+
+impl BackupCommand {
+    pub fn new(client: TodoistClient, profile: &str, output: Option<&str>) -> BackupCommand {
+        BackupCommand {
+            client: client,
+            profile: profile.to_string(),
+            output: output.map(|o| o.to_string()),
+        }
+    }
+}
+
+impl Command for BackupCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let resources = self.client.get_resources(vec!["all"])?;
+        // Archived projects and completed tasks come from other endpoints,
+        // completed ones only for premium users, so the backup goes on
+        // without them
+        let archived_projects = self.client.get_archived_projects().unwrap_or_else(|err| {
+            eprintln!("warning: archived projects skipped: {}", err);
+            Vec::new()
+        });
+        let archived_data = archived_projects
+            .iter()
+            .filter_map(|project| match self.client.get_project_data(project.id) {
+                Ok(data) => Some(data),
+                Err(err) => {
+                    eprintln!("warning: tasks of {} skipped: {}", project.name, err);
+                    None
+                }
+            })
+            .collect();
+        let completed = self
+            .client
+            .get_completed(None, None, None)
+            .unwrap_or_else(|err| {
+                eprintln!("warning: completed tasks skipped: {}", err);
+                Vec::new()
+            });
+        let archive = Archive::new(
+            &self.profile,
+            resources,
+            archived_projects,
+            archived_data,
+            completed,
+        );
+
+        match self.output.as_deref() {
+            Some("-") => {
+                io::stdout().write_all(serde_json::to_string_pretty(&archive)?.as_bytes())?;
+            }
+            output => {
+                let path = match output {
+                    Some(path) => PathBuf::from(path),
+                    None => Archive::default_path(&self.profile)?,
+                };
+                archive.save(&path)?;
+                println!("{}", path.display());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, Write};
+
+use clap::Shell;
+
+use super::super::app::build_app;
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::Command;
+
+// Options completed with values from the cache, by `dothis _complete`
+const DYNAMIC_OPTIONS: [(&str, &str, &str); 2] =
+    [("project", "P", "projects"), ("label", "l", "labels")];
+
+// Prints the completion script generated by clap, extended to complete
+// project and label options dynamically
+pub struct CompletionsCommand {
+    shell: String,
+}
+
+impl CompletionsCommand {
+    pub fn new(shell: &str) -> CompletionsCommand {
+        CompletionsCommand {
+            shell: shell.to_string(),
+        }
+    }
+
+    fn generate(&self, shell: Shell) -> String {
+        let mut script = Vec::new();
+        build_app().gen_completions_to("dothis", shell, &mut script);
+        String::from_utf8_lossy(&script).into_owned()
+    }
+
+    fn bash(&self) -> String {
+        let mut script = self.generate(Shell::Bash);
+        let cases: Vec<String> = DYNAMIC_OPTIONS
+            .iter()
+            .map(|(long, short, resource)| {
+                format!(
+                    "        --{}|-{})\n            local IFS=$'\\n'\n            COMPREPLY=( $(compgen -W \"$(dothis _complete {} 2>/dev/null)\" -- \"${{cur}}\") )\n            return 0\n            ;;",
+                    long, short, resource
+                )
+            })
+            .collect();
+
+        script.push_str(&format!(
+            "
+_dothis_dynamic() {{
+    local cur prev
+    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    case \"${{prev}}\" in
+{}
+    esac
+    _dothis \"$@\"
+}}
+
+complete -F _dothis_dynamic -o bashdefault -o default dothis
+",
+            cases.join("\n")
+        ));
+        script
+    }
+
+    // zsh option specs have no action unless they have possible values, one
+    // calling dothis _complete is added to the dynamic ones
+    fn zsh(&self) -> String {
+        let script = self.generate(Shell::Zsh);
+        let mut lines: Vec<String> = script
+            .lines()
+            .map(|line| {
+                for (long, short, resource) in DYNAMIC_OPTIONS.iter() {
+                    let long_spec = format!("--{}=[", long);
+                    let short_spec = format!("-{}+[", short);
+                    if (line.contains(&long_spec) || line.contains(&short_spec))
+                        && line.ends_with("]' \\")
+                    {
+                        let end = line.len() - " \\".len() - 1;
+                        return format!("{}: :_dothis_{}{}", &line[..end], resource, &line[end..]);
+                    }
+                }
+                line.to_string()
+            })
+            .collect();
+
+        // Helpers have to be defined before the script calls _dothis
+        let call = lines
+            .iter()
+            .rposition(|l| l.trim() == "_dothis \"$@\"")
+            .unwrap_or(lines.len());
+        for (_, _, resource) in DYNAMIC_OPTIONS.iter() {
+            lines.insert(
+                call,
+                format!(
+                    "_dothis_{resource}() {{\n    local -a values\n    values=(\"${{(@f)$(dothis _complete {resource} 2>/dev/null | sed 's/:/\\\\:/g')}}\")\n    _describe '{resource}' values\n}}\n",
+                    resource = resource
+                ),
+            );
+        }
+        lines.join("\n") + "\n"
+    }
+
+    fn fish(&self) -> String {
+        let mut script = self.generate(Shell::Fish);
+        for (long, short, resource) in DYNAMIC_OPTIONS.iter() {
+            script.push_str(&format!(
+                "complete -c dothis -l {} -s {} -x -a '(dothis _complete {} 2>/dev/null)'\n",
+                long, short, resource
+            ));
+        }
+        script
+    }
+}
+
+impl Command for CompletionsCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let script = match self.shell.as_str() {
+            "bash" => self.bash(),
+            "zsh" => self.zsh(),
+            "fish" => self.fish(),
+            _ => return Err(DothisError::UnknownCommand),
+        };
+        io::stdout().write_all(script.as_bytes())?;
+        Ok(())
+    }
+}
+
+// Prints the names of cached resources, one per line. Projects are printed
+// with their full path, so nested projects with the same name can be told apart.
+pub struct CompleteCommand {
+    cache: Cache,
+    resource_type: String,
+}
+
+impl CompleteCommand {
+    pub fn new(cache: Cache, resource_type: &str) -> CompleteCommand {
+        CompleteCommand {
+            cache: cache,
+            resource_type: resource_type.to_string(),
+        }
+    }
+}
+
+impl Command for CompleteCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        match self.resource_type.as_str() {
+            "projects" => {
+                let resolver = self.cache.resolver();
+                for project in self.cache.projects.iter() {
+                    println!("{}", resolver.project_path(project.id).join("/"));
+                }
+            }
+            "labels" => {
+                for label in self.cache.labels.iter() {
+                    println!("{}", label.name);
+                }
+            }
+            _ => return Err(DothisError::UnknownResource),
+        };
+        Ok(())
+    }
+}
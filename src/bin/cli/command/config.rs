@@ -0,0 +1,42 @@
+use clap::ArgMatches;
+
+use super::super::config::Config;
+use super::super::error::DothisError;
+use super::Command;
+
+pub struct ConfigCommand<'a> {
+    config: Config,
+    profile: String,
+    args: ArgMatches<'a>,
+}
+
+impl<'a> ConfigCommand<'a> {
+    pub fn new(config: Config, profile: &str, args: ArgMatches<'a>) -> ConfigCommand<'a> {
+        ConfigCommand {
+            config: config,
+            profile: profile.to_string(),
+            args: args,
+        }
+    }
+}
+
+impl<'a> Command for ConfigCommand<'a> {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        match self.args.subcommand() {
+            ("get", Some(get)) => {
+                let key = get.value_of("key").expect("argument key is required");
+                if let Some(value) = self.config.get(&self.profile, key)? {
+                    println!("{}", value);
+                }
+                Ok(())
+            }
+            ("set", Some(set)) => {
+                let key = set.value_of("key").expect("argument key is required");
+                let value = set.value_of("value").expect("argument value is required");
+                self.config.set(&self.profile, key, value)?;
+                self.config.save()
+            }
+            _ => Err(DothisError::MissingCommand),
+        }
+    }
+}
@@ -0,0 +1,698 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use dothis::api::client::{CommandResult, TodoistClient};
+use dothis::api::resource::{AddItem, Item, MoveItem, NewDueDate, RawCommand, UpdateItem};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::super::query::Query;
+use super::http::{self, Request, READ_TIMEOUT};
+use super::Command;
+
+const RESOURCES: [&str; 6] = [
+    "projects",
+    "sections",
+    "labels",
+    "items",
+    "collaborators",
+    "user",
+];
+
+// Editors send single calls or small batches
+const MAX_BODY: usize = 1024 * 1024;
+
+// Error codes of the JSON-RPC 2.0 specification, and the one of failed calls
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const CALL_FAILED: i32 = -32000;
+
+pub enum Listen {
+    // JSON-RPC requests one per line, answered one per line
+    Socket(String),
+    // JSON-RPC requests as the body of POST requests
+    Http(String),
+}
+
+// Serves the tasks of a profile to editor plugins over JSON-RPC, from a cache
+// kept up to date with incremental syncs. Writes of a batch are sent in a
+// single request, and recorded in the journal to be undone like others.
+pub struct DaemonCommand {
+    daemon: Arc<Mutex<Daemon>>,
+    profile: String,
+    listen: Listen,
+    interval: Duration,
+}
+
+impl DaemonCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        profile: &str,
+        listen: Listen,
+        interval: Duration,
+        default_project: Option<&str>,
+    ) -> DaemonCommand {
+        DaemonCommand {
+            daemon: Arc::new(Mutex::new(Daemon {
+                client: client,
+                cache: cache,
+                journal: journal,
+                default_project: default_project.map(|p| p.to_string()),
+                sync_token: "*".to_string(),
+            })),
+            profile: profile.to_string(),
+            listen: listen,
+            interval: interval,
+        }
+    }
+
+    // Syncs in the background for reads to see changes made elsewhere
+    fn spawn_sync(&self) {
+        let daemon = Arc::clone(&self.daemon);
+        let interval = self.interval;
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            if let Err(err) = lock(&daemon).sync() {
+                eprintln!("warning: {}", err);
+            }
+        });
+    }
+
+    fn serve_socket(&self, path: &str) -> Result<(), DothisError> {
+        // A socket left behind by a daemon that did not exit cleanly is
+        // replaced, one still answering is not
+        if Path::new(path).exists() {
+            match UnixStream::connect(path) {
+                Ok(_) => return Err(DothisError::DaemonRunning(path.to_string())),
+                Err(_) => fs::remove_file(path)?,
+            }
+        }
+        // The daemon acts with the token of the profile, for its user only.
+        // The socket is created that way, instead of being restricted once
+        // others could already connect.
+        let umask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(umask) };
+        let listener = listener?;
+        eprintln!("listening on {}", path);
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let daemon = Arc::clone(&self.daemon);
+                    thread::spawn(move || {
+                        if let Err(err) = serve_lines(&daemon, stream) {
+                            eprintln!("error: {}", err);
+                        }
+                    });
+                }
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    // Any local user can connect to a port, so requests have to carry a
+    // secret only the user of the daemon can read, new for every daemon
+    fn serve_http(&self, address: &str) -> Result<(), DothisError> {
+        if !address.to_socket_addrs()?.all(|a| a.ip().is_loopback()) {
+            return Err(DothisError::NonLocalAddress(address.to_string()));
+        }
+        let secret = Uuid::new_v4().to_simple().to_string();
+        let path = store_secret(&self.profile, &secret)?;
+        let secret = Arc::new(secret);
+
+        let listener = TcpListener::bind(address)?;
+        eprintln!(
+            "listening on http://{}/, with the secret in {}",
+            address,
+            path.display()
+        );
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    let daemon = Arc::clone(&self.daemon);
+                    let secret = Arc::clone(&secret);
+                    thread::spawn(move || {
+                        if let Err(err) = serve_request(&daemon, &secret, &mut stream) {
+                            eprintln!("error: {}", err);
+                        }
+                    });
+                }
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Command for DaemonCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        lock(&self.daemon).sync()?;
+        self.spawn_sync();
+        match &self.listen {
+            Listen::Socket(path) => self.serve_socket(path),
+            Listen::Http(address) => self.serve_http(address),
+        }
+    }
+}
+
+// A panic while handling a call leaves the state as it was before it
+fn lock(daemon: &Mutex<Daemon>) -> MutexGuard<Daemon> {
+    daemon
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn serve_lines(daemon: &Mutex<Daemon>, stream: UnixStream) -> Result<(), DothisError> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = lock(daemon).handle(&line) {
+            writeln!(writer, "{}", response)?;
+        }
+    }
+    Ok(())
+}
+
+// Secrets are written where only the user can read them, like tokens
+fn store_secret(profile: &str, secret: &str) -> Result<PathBuf, DothisError> {
+    let path = match dirs::data_dir() {
+        Some(dir) => dir.join("dothis").join("daemon").join(profile),
+        None => return Err(DothisError::MissingConfigDir),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        fs::set_permissions(parent, fs::Permissions::from_mode(0o700))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)?;
+    // mode only applies to newly created files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    writeln!(file, "{}", secret)?;
+    Ok(path)
+}
+
+// Secrets are compared in constant time
+fn is_authorized(request: &Request, secret: &str) -> bool {
+    let given = match request.header("Authorization") {
+        Some(value) => value.strip_prefix("Bearer ").unwrap_or_default().trim(),
+        None => return false,
+    };
+    given.len() == secret.len()
+        && given
+            .bytes()
+            .zip(secret.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+// Only JSON requests for a local host with the secret of the daemon are
+// answered: browsers cannot send those from other sites without asking
+// first, and pages of rebound domains send their own name
+fn serve_request(
+    daemon: &Mutex<Daemon>,
+    secret: &str,
+    stream: &mut TcpStream,
+) -> Result<(), DothisError> {
+    stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT)))?;
+    let request = match Request::read(stream, MAX_BODY)? {
+        Some(request) => request,
+        None => return http::respond(stream, "413 Payload Too Large", "text/plain", ""),
+    };
+    let is_local = request
+        .header("Host")
+        .map(|h| h.rsplitn(2, ':').last().unwrap_or(h))
+        .map_or(false, |h| {
+            h == "localhost" || h == "127.0.0.1" || h == "[::1]"
+        });
+    let is_json = request
+        .header("Content-Type")
+        .map_or(false, |t| t.starts_with("application/json"));
+
+    match (request.method(), is_local, is_json) {
+        ("POST", true, _) if !is_authorized(&request, secret) => {
+            http::respond(stream, "401 Unauthorized", "text/plain", "")
+        }
+        ("POST", true, true) => {
+            let body = String::from_utf8_lossy(&request.body);
+            match lock(daemon).handle(&body) {
+                Some(response) => {
+                    http::respond(stream, "200 OK", "application/json", &response.to_string())
+                }
+                None => http::respond(stream, "204 No Content", "application/json", ""),
+            }
+        }
+        ("POST", true, false) => {
+            http::respond(stream, "415 Unsupported Media Type", "text/plain", "")
+        }
+        ("POST", false, _) => http::respond(stream, "403 Forbidden", "text/plain", ""),
+        _ => http::respond(stream, "405 Method Not Allowed", "text/plain", ""),
+    }
+}
+
+struct Daemon {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    default_project: Option<String>,
+    sync_token: String,
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    method: String,
+    #[serde(default)]
+    params: Value,
+    // Calls without an id are notifications, which get no response
+    id: Option<Value>,
+}
+
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i32, message: &str) -> RpcError {
+        RpcError {
+            code: code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<DothisError> for RpcError {
+    fn from(err: DothisError) -> RpcError {
+        RpcError::new(CALL_FAILED, &err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RpcError {
+    fn from(err: serde_json::Error) -> RpcError {
+        RpcError::new(INVALID_PARAMS, &err.to_string())
+    }
+}
+
+// Writes are sent with the other writes of their batch, their commands
+// are undone together
+struct PendingWrite {
+    description: String,
+    commands: Vec<RawCommand>,
+    undo: Vec<Vec<RawCommand>>,
+    // None for adds, whose result is the id of the new task
+    result: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    query: Option<String>,
+    project: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AddParams {
+    content: String,
+    project: Option<String>,
+    section: Option<String>,
+    parent: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    due: Option<String>,
+    priority: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct UpdateParams {
+    id: u32,
+    content: Option<String>,
+    due: Option<String>,
+    priority: Option<i32>,
+    labels: Option<Vec<String>>,
+    project: Option<String>,
+    section: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompleteParams {
+    ids: Vec<u32>,
+}
+
+impl Daemon {
+    fn sync(&mut self) -> Result<(), DothisError> {
+        let response = self
+            .client
+            .get_changes(RESOURCES.to_vec(), &self.sync_token)?;
+        let full = self.sync_token == "*";
+        self.sync_token = response.sync_token.clone();
+        match full {
+            true => self.cache.update(response),
+            false => self.cache.merge(response),
+        }
+        self.cache.save()
+    }
+
+    // Answers a call or a batch of calls, None when they were all notifications
+    fn handle(&mut self, body: &str) -> Option<Value> {
+        let calls = match serde_json::from_str::<Value>(body) {
+            Ok(Value::Array(calls)) if !calls.is_empty() => calls,
+            Ok(Value::Array(_)) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(INVALID_REQUEST, "empty batch"),
+                ))
+            }
+            Ok(call) => return self.handle_batch(vec![call]).into_iter().next(),
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, &err.to_string()),
+                ))
+            }
+        };
+        let responses = self.handle_batch(calls);
+        match responses.is_empty() {
+            true => None,
+            false => Some(Value::Array(responses)),
+        }
+    }
+
+    // Writes are prepared and sent first, reads see the tasks they changed
+    fn handle_batch(&mut self, calls: Vec<Value>) -> Vec<Value> {
+        let mut results: Vec<(Option<Value>, Result<Value, RpcError>)> = Vec::new();
+        let mut reads = Vec::new();
+        let mut writes = Vec::new();
+        for call in calls.into_iter() {
+            let request: RpcRequest = match serde_json::from_value(call) {
+                Ok(request) => request,
+                Err(err) => {
+                    let error = RpcError::new(INVALID_REQUEST, &err.to_string());
+                    results.push((Some(Value::Null), Err(error)));
+                    continue;
+                }
+            };
+            let index = results.len();
+            let prepared = match request.method.as_str() {
+                "list" | "sync" => {
+                    reads.push((index, request.method, request.params));
+                    Ok(None)
+                }
+                "add" => self.prepare_add(request.params).map(Some),
+                "update" => self.prepare_update(request.params).map(Some),
+                "complete" => self.prepare_complete(request.params).map(Some),
+                other => Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    &format!("unknown method {}", other),
+                )),
+            };
+            if let Ok(Some(write)) = prepared {
+                writes.push((index, write));
+                results.push((request.id, Ok(Value::Null)));
+            } else {
+                results.push((request.id, prepared.map(|_| Value::Null)));
+            }
+        }
+
+        if !writes.is_empty() {
+            self.send(&mut results, writes);
+        }
+        for (index, method, params) in reads.into_iter() {
+            results[index].1 = match method.as_str() {
+                "list" => self.list(params),
+                _ => self.sync().map(|_| Value::Null).map_err(RpcError::from),
+            };
+        }
+
+        results
+            .into_iter()
+            .filter_map(|(id, result)| {
+                id.map(|id| match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+                    Err(err) => error_response(id, err),
+                })
+            })
+            .collect()
+    }
+
+    fn send(
+        &mut self,
+        results: &mut Vec<(Option<Value>, Result<Value, RpcError>)>,
+        writes: Vec<(usize, PendingWrite)>,
+    ) {
+        let commands: Vec<RawCommand> = writes
+            .iter()
+            .flat_map(|(_, write)| write.commands.iter().cloned())
+            .collect();
+        let mut command_results = match self.client.sync_batch(&commands) {
+            Ok(command_results) => command_results.into_iter(),
+            Err(err) => {
+                let message = DothisError::from(err).to_string();
+                for (index, _) in writes.iter() {
+                    results[*index].1 = Err(RpcError::new(CALL_FAILED, &message));
+                }
+                return;
+            }
+        };
+
+        for (index, write) in writes.into_iter() {
+            let write_results: Vec<CommandResult> = command_results
+                .by_ref()
+                .take(write.commands.len())
+                .collect();
+            let errors: Vec<String> = write_results
+                .iter()
+                .filter_map(|r| r.as_ref().err().cloned())
+                .collect();
+
+            // Added tasks are undone by deleting them, once their id is known
+            let (undo, result) = match (write.result, write_results.first()) {
+                (None, Some(Ok(Some(id)))) => {
+                    (vec![Inverse::delete("tasks", *id)], json!({ "id": id }))
+                }
+                (result, _) => (write.undo, result.unwrap_or(Value::Null)),
+            };
+            if let Err(err) = self
+                .journal
+                .record_batch(&write.description, &write_results, undo)
+            {
+                eprintln!("warning: {}", err);
+            }
+
+            results[index].1 = match errors.is_empty() {
+                true => Ok(result),
+                false => Err(RpcError::new(CALL_FAILED, &errors.join(", "))),
+            };
+        }
+
+        // Tasks are read back for the cache to hold them as the API does
+        if let Err(err) = self.sync() {
+            eprintln!("warning: {}", err);
+        }
+    }
+
+    fn resolve(
+        &mut self,
+        resource_type: &str,
+        query: Option<&str>,
+    ) -> Result<Option<u32>, RpcError> {
+        match query {
+            Some(query) => Ok(Some(self.cache.resolve(
+                &self.client,
+                resource_type,
+                query,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    fn resolve_labels(&mut self, labels: &[String]) -> Result<Vec<u32>, RpcError> {
+        labels
+            .iter()
+            .map(|label| Ok(self.cache.resolve(&self.client, "labels", label)?))
+            .collect()
+    }
+
+    fn list(&mut self, params: Value) -> Result<Value, RpcError> {
+        let params: ListParams = serde_json::from_value(or_empty(params))?;
+        let query = match &params.query {
+            Some(query) => Some(Query::parse(&self.client, &mut self.cache, query)?),
+            None => None,
+        };
+        let project = self.resolve("projects", params.project.as_deref())?;
+
+        let tasks: Vec<&Item> = self
+            .cache
+            .items
+            .iter()
+            .filter(|item| !item.checked && !item.is_deleted)
+            .filter(|item| project.map_or(true, |id| item.project_id == id))
+            .filter(|item| query.as_ref().map_or(true, |q| q.matches(item)))
+            .collect();
+        Ok(json!(tasks))
+    }
+
+    // Tasks without a project go where add puts them
+    fn prepare_add(&mut self, params: Value) -> Result<PendingWrite, RpcError> {
+        let params: AddParams = serde_json::from_value(params)?;
+        check_priority(params.priority)?;
+        // Tasks placed in a section or under a parent go to the project of it
+        // when none is given, like with add
+        let section_id = self.resolve("sections", params.section.as_deref())?;
+        let parent_id = self.resolve("tasks", params.parent.as_deref())?;
+        let placed = section_id.is_some() || parent_id.is_some();
+        let project = params
+            .project
+            .clone()
+            .or_else(|| self.default_project.clone().filter(|_| !placed));
+        let project_id = match project {
+            Some(project) => Some(self.cache.resolve(&self.client, "projects", &project)?),
+            None if placed => None,
+            None => self.cache.user.as_ref().and_then(|u| u.inbox_project),
+        };
+        let labels = self.resolve_labels(&params.labels)?;
+
+        let item = AddItem {
+            project_id: project_id,
+            section_id: section_id,
+            parent_id: parent_id,
+            labels: Some(labels).filter(|l| !l.is_empty()),
+            content: params.content.clone(),
+            description: None,
+            due: params.due.as_deref().map(NewDueDate::from_string),
+            priority: params.priority,
+            child_order: None,
+            day_order: None,
+            collapsed: None,
+            assigned_by_uid: None,
+            responsible_uid: None,
+            auto_reminder: None,
+            auto_parse_labels: None,
+        };
+        Ok(PendingWrite {
+            description: format!("add task {}", params.content),
+            commands: vec![RawCommand::add("item_add", json!(item), Uuid::new_v4())],
+            undo: vec![Vec::new()],
+            result: None,
+        })
+    }
+
+    fn prepare_update(&mut self, params: Value) -> Result<PendingWrite, RpcError> {
+        let params: UpdateParams = serde_json::from_value(params)?;
+        check_priority(params.priority)?;
+        self.cache.ensure(&self.client, "tasks", &[params.id])?;
+        let id = params.id;
+        let labels = match &params.labels {
+            Some(labels) => Some(self.resolve_labels(labels)?),
+            None => None,
+        };
+
+        let mut commands = vec![RawCommand::new(
+            "item_update",
+            json!(UpdateItem {
+                id: id,
+                content: params.content,
+                due: params.due.as_deref().map(NewDueDate::from_string),
+                priority: params.priority,
+                labels: labels,
+                ..UpdateItem::default()
+            }),
+        )];
+        let project_id = self.resolve("projects", params.project.as_deref())?;
+        let section_id = self.resolve("sections", params.section.as_deref())?;
+        if project_id.is_some() || section_id.is_some() {
+            let move_item = MoveItem {
+                id: id,
+                project_id: project_id.filter(|_| section_id.is_none()),
+                section_id: section_id,
+                parent_id: None,
+            };
+            commands.push(RawCommand::new("item_move", json!(move_item)));
+        }
+
+        let mut undo = vec![Inverse::restore(&self.cache, "tasks", id)];
+        undo.resize(commands.len(), Vec::new());
+        Ok(PendingWrite {
+            description: format!("edit task {}", self.cache.name("tasks", id)),
+            commands: commands,
+            undo: undo,
+            result: Some(json!({ "id": id })),
+        })
+    }
+
+    fn prepare_complete(&mut self, params: Value) -> Result<PendingWrite, RpcError> {
+        let params: CompleteParams = serde_json::from_value(params)?;
+        self.cache.ensure(&self.client, "tasks", &params.ids)?;
+        let names: Vec<String> = params
+            .ids
+            .iter()
+            .map(|id| self.cache.name("tasks", *id))
+            .collect();
+
+        Ok(PendingWrite {
+            description: format!("done {}", names.join(", ")),
+            commands: params
+                .ids
+                .iter()
+                .map(|id| RawCommand::new("item_close", json!({ "id": id })))
+                .collect(),
+            undo: params
+                .ids
+                .iter()
+                .map(|id| Inverse::uncomplete(&self.cache, *id))
+                .collect(),
+            result: Some(json!({ "ids": params.ids })),
+        })
+    }
+}
+
+// Priorities are those of the API, 4 is the most urgent
+fn check_priority(priority: Option<i32>) -> Result<(), RpcError> {
+    match priority {
+        Some(priority) if priority < 1 || priority > 4 => Err(RpcError::new(
+            INVALID_PARAMS,
+            "priority must be between 1 and 4",
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Params can be left out of calls taking only optional ones
+fn or_empty(params: Value) -> Value {
+    match params {
+        Value::Null => json!({}),
+        params => params,
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": err.code, "message": err.message },
+        "id": id,
+    })
+}
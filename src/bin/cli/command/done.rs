@@ -0,0 +1,54 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::CloseItem;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{report_batch, Command};
+
+pub struct DoneCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    tasks: Vec<u32>,
+}
+
+impl DoneCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        tasks: Vec<u32>,
+    ) -> DoneCommand {
+        DoneCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            tasks: tasks,
+        }
+    }
+}
+
+impl Command for DoneCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        self.cache.ensure(&self.client, "tasks", &self.tasks)?;
+        let undo = self
+            .tasks
+            .iter()
+            .map(|id| Inverse::uncomplete(&self.cache, *id))
+            .collect();
+        let names: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|id| self.cache.name("tasks", *id))
+            .collect();
+
+        let commands: Vec<CloseItem> = self.tasks.iter().map(|id| CloseItem { id: *id }).collect();
+        let results = self.client.sync_batch(&commands)?;
+        self.journal
+            .record_batch(&format!("done {}", names.join(", ")), &results, undo)?;
+
+        let subjects: Vec<String> = self.tasks.iter().map(|id| id.to_string()).collect();
+        report_batch(&subjects, results)
+    }
+}
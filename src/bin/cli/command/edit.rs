@@ -0,0 +1,329 @@
+use std::convert::TryFrom;
+
+use clap::ArgMatches;
+use uuid::Uuid;
+
+use dothis::api::client::{TodoistClient, TodoistQueryBuilder};
+use dothis::api::resource::{
+    AddNote, Color, MoveItem, MoveProject, NewDueDate, UpdateItem, UpdateLabel, UpdateNote,
+    UpdateProject,
+};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::editor::{self, TaskDocument};
+use super::{check_response, description_of, id_of, resolve_arg, resolve_args, Command};
+
+// Updates a resource, moving it as well if a new parent was given. Both
+// changes are sent in the same request.
+pub struct EditCommand<'a> {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    resource_type: String,
+    args: ArgMatches<'a>,
+    // Task or project picked interactively, instead of given as an argument
+    picked: Option<u32>,
+    // Comment added to a task edited in an editor, deleted on undo
+    note: Option<Uuid>,
+}
+
+impl<'a> EditCommand<'a> {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        resource_type: &str,
+        args: ArgMatches<'a>,
+        picked: Option<u32>,
+    ) -> EditCommand<'a> {
+        EditCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            resource_type: resource_type.to_string(),
+            args: args,
+            picked: picked,
+            note: None,
+        }
+    }
+
+    fn resolve(&mut self, resource_type: &str, name: &str) -> Result<Option<u32>, DothisError> {
+        resolve_arg(
+            &self.client,
+            &mut self.cache,
+            &self.args,
+            resource_type,
+            name,
+        )
+    }
+
+    fn is_any_present(&self, names: &[&str]) -> bool {
+        names.iter().any(|name| self.args.is_present(name))
+    }
+
+    fn favorite(&self) -> Option<bool> {
+        match (
+            self.args.is_present("favorite"),
+            self.args.is_present("unfavorite"),
+        ) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        self.args
+            .value_of("color")
+            .map_or(None, |c| Color::try_from(c).ok())
+    }
+
+    fn add_task_commands(&mut self, builder: &mut TodoistQueryBuilder) -> Result<u32, DothisError> {
+        let id = match self.picked {
+            Some(id) => id,
+            None => self.resolve("tasks", "task")?.expect("task is required"),
+        };
+        if self.args.is_present("editor") {
+            if self.is_any_present(&[
+                "content",
+                "description",
+                "description-file",
+                "priority",
+                "due",
+                "label",
+                "project",
+                "section",
+                "parent",
+            ]) {
+                return Err(DothisError::EditorConflict);
+            }
+            self.add_document_commands(builder, id)?;
+            return Ok(id);
+        }
+
+        if self.is_any_present(&[
+            "content",
+            "description",
+            "description-file",
+            "priority",
+            "due",
+            "label",
+        ]) {
+            let update = UpdateItem {
+                id: id,
+                content: self.args.value_of("content").map(|c| c.to_string()),
+                description: description_of(&self.args)?,
+                due: self.args.value_of("due").map(NewDueDate::from_string),
+                priority: self
+                    .args
+                    .value_of("priority")
+                    .map_or(None, |v| v.parse::<i32>().ok()),
+                labels: resolve_args(&self.client, &mut self.cache, &self.args, "labels", "label")?,
+                ..UpdateItem::default()
+            };
+            builder.add(&update, None, None);
+        }
+
+        if self.is_any_present(&["project", "section", "parent"]) {
+            let move_item = MoveItem {
+                id: id,
+                project_id: self.resolve("projects", "project")?,
+                section_id: self.resolve("sections", "section")?,
+                parent_id: self.resolve("tasks", "parent")?,
+            };
+            builder.add(&move_item, None, None);
+        }
+        Ok(id)
+    }
+
+    // Fields changed in the editor are updated, moving the task if its project
+    // or section changed, and text written below the comments is added as one
+    fn add_document_commands(
+        &mut self,
+        builder: &mut TodoistQueryBuilder,
+        id: u32,
+    ) -> Result<(), DothisError> {
+        let response = self.client.get_resources(vec![
+            "items",
+            "projects",
+            "sections",
+            "labels",
+            "notes",
+            "collaborators",
+            "user",
+        ])?;
+        self.cache.update(response);
+        self.cache.save()?;
+        let original = match self.cache.item(id) {
+            Some(item) => TaskDocument::of_item(&self.cache, item),
+            None => return Err(DothisError::EmptyResponseError),
+        };
+        let comments = editor::comments(&self.cache, id);
+        let edited = match editor::edit(&original, Some(&comments))? {
+            Some(edited) => edited,
+            None => return Ok(()),
+        };
+
+        let due = match (edited.due == original.due, edited.due.is_empty()) {
+            (true, _) => None,
+            (false, true) => Some(NewDueDate::from_string("no date")),
+            (false, false) => Some(NewDueDate::from_string(&edited.due)),
+        };
+        let labels = match edited.labels == original.labels {
+            true => None,
+            false => Some(edited.label_ids(&self.client, &mut self.cache)?),
+        };
+        let update = UpdateItem {
+            id: id,
+            content: Some(edited.content.clone()).filter(|c| *c != original.content),
+            description: Some(edited.description.clone()).filter(|d| *d != original.description),
+            due: due,
+            priority: Some(edited.priority).filter(|p| *p != original.priority),
+            labels: labels,
+            ..UpdateItem::default()
+        };
+        if update.content.is_some()
+            || update.description.is_some()
+            || update.due.is_some()
+            || update.priority.is_some()
+            || update.labels.is_some()
+        {
+            builder.add(&update, None, None);
+        }
+
+        // Tasks without a project stay where they are
+        let moved = !edited.project.is_empty()
+            && (edited.project != original.project || edited.section != original.section);
+        if moved {
+            let move_item = match edited.section_id(&self.client, &mut self.cache)? {
+                Some(section_id) => MoveItem {
+                    id: id,
+                    project_id: None,
+                    section_id: Some(section_id),
+                    parent_id: None,
+                },
+                None => MoveItem {
+                    id: id,
+                    project_id: Some(self.cache.resolve(
+                        &self.client,
+                        "projects",
+                        &edited.project,
+                    )?),
+                    section_id: None,
+                    parent_id: None,
+                },
+            };
+            builder.add(&move_item, None, None);
+        }
+
+        if !edited.comment.is_empty() {
+            let temp_id = Uuid::new_v4();
+            let note = AddNote {
+                item_id: id,
+                content: edited.comment,
+                uids_to_notify: None,
+            };
+            builder.add(&note, None, Some(temp_id));
+            self.note = Some(temp_id);
+        }
+        Ok(())
+    }
+
+    fn add_project_commands(
+        &mut self,
+        builder: &mut TodoistQueryBuilder,
+    ) -> Result<u32, DothisError> {
+        let id = match self.picked {
+            Some(id) => id,
+            None => self
+                .resolve("projects", "project")?
+                .expect("project is required"),
+        };
+
+        if self.is_any_present(&["name", "color", "favorite", "unfavorite"]) {
+            let update = UpdateProject {
+                id: id,
+                name: self.args.value_of("name").map(|n| n.to_string()),
+                color: self.color(),
+                is_favorite: self.favorite(),
+                ..UpdateProject::default()
+            };
+            builder.add(&update, None, None);
+        }
+
+        if let Some(parent_id) = self.resolve("projects", "parent")? {
+            let move_project = MoveProject {
+                id: id,
+                parent_id: Some(parent_id),
+            };
+            builder.add(&move_project, None, None);
+        }
+        Ok(id)
+    }
+
+    fn add_label_commands(
+        &mut self,
+        builder: &mut TodoistQueryBuilder,
+    ) -> Result<u32, DothisError> {
+        let id = self.resolve("labels", "label")?.expect("label is required");
+        let update = UpdateLabel {
+            id: id,
+            name: self.args.value_of("name").map(|n| n.to_string()),
+            color: self.color(),
+            is_favorite: self.favorite(),
+            ..UpdateLabel::default()
+        };
+        builder.add(&update, None, None);
+        Ok(id)
+    }
+
+    fn add_note_commands(&self, builder: &mut TodoistQueryBuilder) -> Result<u32, DothisError> {
+        let id = id_of(&self.args, "id").expect("note id is required");
+        let update = UpdateNote {
+            id: id,
+            content: self
+                .args
+                .value_of("content")
+                .expect("note content is required")
+                .to_string(),
+        };
+        builder.add(&update, None, None);
+        Ok(id)
+    }
+}
+
+impl<'a> Command for EditCommand<'a> {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let mut builder = self.client.builder();
+        let id = match self.resource_type.as_str() {
+            "tasks" => self.add_task_commands(&mut builder)?,
+            "projects" => self.add_project_commands(&mut builder)?,
+            "labels" => self.add_label_commands(&mut builder)?,
+            "notes" => self.add_note_commands(&mut builder)?,
+            _ => return Err(DothisError::UnknownResource),
+        };
+        // Documents left unchanged in the editor change nothing
+        if builder.is_empty() {
+            return Ok(());
+        }
+        self.cache
+            .ensure(&self.client, &self.resource_type, &[id])?;
+        let mut undo = Inverse::restore(&self.cache, &self.resource_type, id);
+
+        let response = self.client.sync(&builder.build())?;
+        check_response(&response)?;
+        if let Some(temp_id) = self.note {
+            if let Some(note_id) = response.temp_id_mapping.get(&temp_id.to_string()) {
+                undo.extend(Inverse::delete("notes", *note_id));
+            }
+        }
+        let description = format!(
+            "edit {} {}",
+            self.resource_type.trim_end_matches('s'),
+            self.cache.name(&self.resource_type, id)
+        );
+        self.journal.record(&description, undo)
+    }
+}
@@ -0,0 +1,382 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::process;
+
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::Item;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::display_date;
+
+// Separates the description from the comments, which are quoted
+const COMMENTS: &str = "--- comments ---";
+const COMMENTS_HELP: &str =
+    "> Comments are quoted, text written below them is added as a new comment.";
+
+// A task as edited in a text editor: fields in a front matter, then the
+// description, then the comments
+#[derive(Default)]
+pub struct TaskDocument {
+    pub content: String,
+    pub project: String,
+    pub section: String,
+    pub labels: Vec<String>,
+    pub due: String,
+    pub priority: i32,
+    pub description: String,
+    // Comment written below the existing ones
+    pub comment: String,
+}
+
+impl TaskDocument {
+    pub fn of_item(cache: &Cache, item: &Item) -> TaskDocument {
+        TaskDocument {
+            content: item.content.clone(),
+            project: cache.resolver().project_path(item.project_id).join("/"),
+            section: item
+                .section_id
+                .and_then(|id| cache.section(id))
+                .map_or(String::new(), |s| s.name.clone()),
+            labels: item
+                .labels
+                .iter()
+                .filter_map(|id| cache.label(*id))
+                .map(|l| l.name.clone())
+                .collect(),
+            due: item
+                .due
+                .as_ref()
+                .map_or(String::new(), |d| d.string.clone()),
+            priority: item.priority,
+            description: normalize(&item.description),
+            comment: String::new(),
+        }
+    }
+
+    // Labels are resolved by name
+    pub fn label_ids(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+    ) -> Result<Vec<u32>, DothisError> {
+        let mut ids = Vec::new();
+        for label in self.labels.iter() {
+            ids.push(cache.resolve(client, "labels", label)?);
+        }
+        Ok(ids)
+    }
+
+    // Sections are resolved by name within the project of the task
+    pub fn section_id(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+    ) -> Result<Option<u32>, DothisError> {
+        let query = match (self.project.is_empty(), self.section.is_empty()) {
+            (_, true) => return Ok(None),
+            (true, false) => self.section.clone(),
+            (false, false) => format!("{}/{}", self.project, self.section),
+        };
+        Ok(Some(cache.resolve(client, "sections", &query)?))
+    }
+
+    // Comments are only written for tasks that exist, new ones have none
+    fn render(&self, comments: Option<&[String]>) -> String {
+        let labels: Vec<String> = self.labels.iter().map(|l| format!("@{}", l)).collect();
+        let mut text = format!(
+            "---\n\
+             content: {}\n\
+             project: {}\n\
+             section: {}\n\
+             labels: {}\n\
+             due: {}\n\
+             priority: {}\n\
+             ---\n\
+             {}\n",
+            self.content,
+            self.project,
+            self.section,
+            labels.join(" "),
+            self.due,
+            self.priority,
+            self.description
+                .lines()
+                .map(escape)
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+        if let Some(comments) = comments {
+            text.push_str(&format!("\n{}\n{}\n", COMMENTS, COMMENTS_HELP));
+            for comment in comments.iter() {
+                text.push_str(">\n");
+                for line in comment.lines() {
+                    text.push_str(&format!("> {}\n", line));
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn parse(text: &str) -> Result<TaskDocument, String> {
+        let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+        if lines.next().map(|line| line.trim_end()) != Some("---") {
+            return Err("the document does not start with ---".to_string());
+        }
+
+        let mut document = TaskDocument {
+            priority: 1,
+            ..TaskDocument::default()
+        };
+        let mut closed = false;
+        for line in &mut lines {
+            if line.trim_end() == "---" {
+                closed = true;
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = match line.find(':') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => return Err(format!("no field in \"{}\"", line)),
+            };
+            match key {
+                "content" => document.content = value.to_string(),
+                "project" => document.project = value.to_string(),
+                "section" => document.section = value.to_string(),
+                "labels" => {
+                    document.labels = value
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .map(|l| l.trim_start_matches('@'))
+                        .filter(|l| !l.is_empty())
+                        .map(|l| l.to_string())
+                        .collect()
+                }
+                "due" => document.due = value.to_string(),
+                "priority" => {
+                    document.priority = match value.parse::<i32>() {
+                        Ok(priority) if (1..=4).contains(&priority) => priority,
+                        _ if value.is_empty() => 1,
+                        _ => return Err(format!("priority {} is not from 1 to 4", value)),
+                    }
+                }
+                other => return Err(format!("unknown field {}", other)),
+            }
+        }
+        if !closed {
+            return Err("the fields are not closed with ---".to_string());
+        }
+        if document.content.is_empty() {
+            return Err("the task has no content".to_string());
+        }
+
+        let mut description = Vec::new();
+        let mut comment = Vec::new();
+        let mut below_comments = false;
+        for line in lines {
+            match (below_comments, line.trim_end() == COMMENTS) {
+                (false, true) => below_comments = true,
+                (false, false) => description.push(unescape(line)),
+                (true, _) if line.starts_with('>') => (),
+                (true, _) => comment.push(line),
+            }
+        }
+        document.description = normalize(&description.join("\n"));
+        document.comment = normalize(&comment.join("\n"));
+        Ok(document)
+    }
+}
+
+// Documents keep neither the blank lines around text nor carriage returns, so
+// descriptions are compared the way they are read back
+fn normalize(text: &str) -> String {
+    text.lines()
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// Description lines that read like the separator of the comments are written
+// with a backslash in front, and those that already have some get one more
+fn escape(line: &str) -> String {
+    match line.trim_end().trim_start_matches('\\') == COMMENTS {
+        true => format!("\\{}", line),
+        false => line.to_string(),
+    }
+}
+
+fn unescape(line: &str) -> &str {
+    match line.starts_with('\\') && line.trim_end().trim_start_matches('\\') == COMMENTS {
+        true => &line[1..],
+        false => line,
+    }
+}
+
+// The comments of a task as shown below its description
+pub fn comments(cache: &Cache, item_id: u32) -> Vec<String> {
+    cache
+        .notes
+        .iter()
+        .filter(|note| note.item_id == item_id && !note.is_deleted)
+        .map(|note| {
+            format!(
+                "{} {}:\n{}",
+                display_date(&note.posted, None, cache.user.as_ref()),
+                cache.user_name(note.posted_uid),
+                note.content
+            )
+        })
+        .collect()
+}
+
+// Opens the document in the editor of the user, returning it as saved, or
+// nothing if it was left unchanged. Documents that cannot be read back are
+// kept, for the changes not to be lost.
+pub fn edit(
+    document: &TaskDocument,
+    comments: Option<&[String]>,
+) -> Result<Option<TaskDocument>, DothisError> {
+    let path = env::temp_dir().join(format!("dothis-{}.md", Uuid::new_v4()));
+    let original = document.render(comments);
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?
+        .write_all(original.as_bytes())?;
+
+    let edited = run_editor(&path).and_then(|_| Ok(fs::read_to_string(&path)?));
+    let edited = match edited {
+        Ok(edited) => edited,
+        Err(err) => {
+            fs::remove_file(&path)?;
+            return Err(err);
+        }
+    };
+    if edited == original {
+        fs::remove_file(&path)?;
+        return Ok(None);
+    }
+    // New tasks have no comments to write below
+    let parsed = TaskDocument::parse(&edited).and_then(|document| {
+        match comments.is_none() && !document.comment.is_empty() {
+            true => Err("comments cannot be added to new tasks".to_string()),
+            false => Ok(document),
+        }
+    });
+    match parsed {
+        Ok(document) => {
+            fs::remove_file(&path)?;
+            Ok(Some(document))
+        }
+        Err(reason) => Err(DothisError::InvalidDocument(
+            path.to_string_lossy().to_string(),
+            reason,
+        )),
+    }
+}
+
+// Editors are shell commands, like "code --wait"
+fn run_editor(path: &Path) -> Result<(), DothisError> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(DothisError::EditorError(editor)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> TaskDocument {
+        TaskDocument {
+            content: "Release 1.2".to_string(),
+            project: "Work/Backend".to_string(),
+            section: "Doing".to_string(),
+            labels: vec!["waiting".to_string(), "deploy".to_string()],
+            due: "every friday".to_string(),
+            priority: 3,
+            description: "Steps:\n\n- tag\n- publish".to_string(),
+            comment: String::new(),
+        }
+    }
+
+    #[test]
+    fn reads_rendered_documents_back() {
+        let comments = vec!["2020-01-31 Ann:\nLooks good".to_string()];
+        let parsed = TaskDocument::parse(&document().render(Some(&comments))).unwrap();
+        let expected = document();
+        assert_eq!(parsed.content, expected.content);
+        assert_eq!(parsed.project, expected.project);
+        assert_eq!(parsed.section, expected.section);
+        assert_eq!(parsed.labels, expected.labels);
+        assert_eq!(parsed.due, expected.due);
+        assert_eq!(parsed.priority, expected.priority);
+        assert_eq!(parsed.description, expected.description);
+        assert_eq!(parsed.comment, "");
+    }
+
+    #[test]
+    fn keeps_separators_written_in_descriptions() {
+        let mut written = document();
+        written.description = format!("Before\n{}\n\\{} \nAfter", COMMENTS, COMMENTS);
+        let parsed = TaskDocument::parse(&written.render(Some(&[]))).unwrap();
+        assert_eq!(parsed.description, written.description);
+        assert_eq!(parsed.comment, "");
+    }
+
+    #[test]
+    fn reads_descriptions_back_normalized() {
+        let mut written = document();
+        written.description = "\n  Indented\r\nlast line  \n".to_string();
+        let parsed = TaskDocument::parse(&written.render(None)).unwrap();
+        assert_eq!(parsed.description, normalize(&written.description));
+        assert_eq!(parsed.description, "Indented\nlast line");
+    }
+
+    #[test]
+    fn reads_new_comments() {
+        let comments = vec!["2020-01-31 Ann:\nLooks good".to_string()];
+        let text = format!("{}Shipped\n", document().render(Some(&comments)));
+        assert_eq!(TaskDocument::parse(&text).unwrap().comment, "Shipped");
+    }
+
+    #[test]
+    fn reads_fields_loosely() {
+        let text = "\n---\ncontent: Call\n\nlabels: @a, b\npriority:\n---\n";
+        let parsed = TaskDocument::parse(text).unwrap();
+        assert_eq!(parsed.content, "Call");
+        assert_eq!(parsed.labels, vec!["a", "b"]);
+        assert_eq!(parsed.priority, 1);
+        assert_eq!(parsed.description, "");
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(TaskDocument::parse("content: Call\n").is_err());
+        assert!(TaskDocument::parse("---\ncontent: Call\n").is_err());
+        assert!(TaskDocument::parse("---\ncontent:\n---\n").is_err());
+        assert!(TaskDocument::parse("---\ncontent: Call\ncolor: red\n---\n").is_err());
+        assert!(TaskDocument::parse("---\ncontent: Call\npriority: 5\n---\n").is_err());
+        assert!(TaskDocument::parse("---\ncontent: Call\nno field\n---\n").is_err());
+    }
+}
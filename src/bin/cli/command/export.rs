@@ -0,0 +1,180 @@
+use std::io::{self, Write};
+
+use clap::ArgMatches;
+
+use dothis::api::client::TodoistClient;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::export;
+use super::super::query::Query;
+use super::Command;
+
+// Prints tasks in a format other tools read, selected by project and query
+pub struct ExportCommand<'a> {
+    client: TodoistClient,
+    cache: Cache,
+    format: String,
+    args: ArgMatches<'a>,
+    project: Option<u32>,
+    query: Option<Query>,
+}
+
+impl<'a> ExportCommand<'a> {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        format: &str,
+        args: ArgMatches<'a>,
+        project: Option<u32>,
+        query: Option<Query>,
+    ) -> ExportCommand<'a> {
+        ExportCommand {
+            client: client,
+            cache: cache,
+            format: format.to_string(),
+            args: args,
+            project: project,
+            query: query,
+        }
+    }
+}
+
+impl<'a> ExportCommand<'a> {
+    fn export(&mut self) -> Result<String, DothisError> {
+        let mut resource_types = vec!["items", "projects", "labels"];
+        match self.format.as_str() {
+            "markdown" | "org" => resource_types.extend(&["sections", "notes"]),
+            "taskwarrior" => resource_types.push("notes"),
+            _ => (),
+        }
+        let response = self.client.get_resources(resource_types)?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        // Documents have the tasks of subprojects too, under their own heading
+        let completed = self.format != "ical";
+        let project = match self.format.as_str() {
+            "markdown" | "org" => None,
+            _ => self.project,
+        };
+        if completed {
+            self.add_completed(project);
+        }
+        let tasks = export::select(&self.cache, project, self.query.as_ref(), completed);
+        Ok(match self.format.as_str() {
+            "markdown" => export::markdown(&tasks, &self.cache, self.project),
+            "org" => export::org(&tasks, &self.cache, self.project),
+            "ical" => export::ical(&tasks, &self.cache, self.args.is_present("events")),
+            "taskwarrior" => export::taskwarrior(&tasks, &self.cache),
+            "todotxt" => export::todotxt(&tasks, &self.cache),
+            _ => return Err(DothisError::UnknownCommand),
+        })
+    }
+
+    // Completed tasks are fetched apart, after the cache is saved, as syncs
+    // leave them out. Only premium users have access to them, others export
+    // their open tasks. A recurring task stays open once completed, and is
+    // exported as such.
+    fn add_completed(&mut self, project: Option<u32>) {
+        let completed = self
+            .client
+            .get_completed(project, None, None)
+            .unwrap_or_else(|err| {
+                eprintln!("warning: completed tasks skipped: {}", err);
+                Vec::new()
+            });
+        for completed in completed.iter() {
+            if !self.cache.items.iter().any(|t| t.id == completed.task_id) {
+                self.cache.items.push(export::completed_item(completed));
+            }
+        }
+    }
+}
+
+impl<'a> Command for ExportCommand<'a> {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let output = self.export()?;
+        io::stdout().write_all(output.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::net::TcpListener;
+    use std::process;
+    use std::thread;
+
+    use serde_json::Value;
+
+    use super::super::http::{self, Request};
+    use super::*;
+
+    const SYNC: &str = r#"{
+        "sync_token": "token",
+        "full_sync": true,
+        "temp_id_mapping": {},
+        "projects": [{"id": 7, "name": "Inbox", "color": 30, "child_order": 1,
+            "collapsed": 0, "shared": false, "is_deleted": 0, "is_archived": 0,
+            "is_favorite": 0, "inbox_project": true}],
+        "items": [{"id": 1, "user_id": 3, "project_id": 7, "content": "Call mom",
+            "priority": 1, "child_order": 1, "day_order": 1, "collapsed": 0,
+            "labels": [], "checked": 0, "in_history": 0, "is_deleted": 0,
+            "date_added": "2020-01-30T10:00:00Z"}],
+        "labels": []
+    }"#;
+
+    // The second task is recurring, completed once and still open
+    const COMPLETED: &str = r#"{"items": [
+        {"id": 90, "task_id": 2, "user_id": 3, "project_id": 7, "section_id": null,
+            "content": "Pay rent", "completed_date": "2020-02-02T09:00:00Z"},
+        {"id": 91, "task_id": 1, "user_id": 3, "project_id": 7, "section_id": null,
+            "content": "Call mom", "completed_date": "2020-01-31T09:00:00Z"}
+    ]}"#;
+
+    // Answers syncs and completed/get_all requests like the API
+    fn serve(listener: TcpListener) {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let request = Request::read(&mut stream, 1024 * 1024).unwrap().unwrap();
+            let body = match request.head.split(' ').nth(1) {
+                Some("/completed/get_all") => COMPLETED,
+                _ => SYNC,
+            };
+            http::respond(&mut stream, "200 OK", "application/json", body).unwrap();
+        }
+    }
+
+    fn export(url: &str, format: &str) -> String {
+        let client = TodoistClient::with_url("token", url);
+        let cache = Cache::load("export").unwrap();
+        ExportCommand::new(client, cache, format, ArgMatches::default(), None, None)
+            .export()
+            .unwrap()
+    }
+
+    #[test]
+    fn exports_completed_tasks() {
+        let dir = env::temp_dir().join(format!("dothis-export-{}", process::id()));
+        env::set_var("XDG_CACHE_HOME", &dir);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sync", listener.local_addr().unwrap());
+        thread::spawn(move || serve(listener));
+
+        assert_eq!(
+            export(&url, "todotxt"),
+            "2020-01-30 Call mom +Inbox\nx 2020-02-02 2020-02-02 Pay rent +Inbox\n"
+        );
+        let markdown = export(&url, "markdown");
+        assert!(markdown.contains("- [ ] Call mom\n"));
+        assert!(markdown.contains("- [x] Pay rent\n"));
+        let tasks: Vec<Value> = serde_json::from_str(&export(&url, "taskwarrior")).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1]["status"], "completed");
+        assert_eq!(tasks[1]["end"], "20200202T090000Z");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
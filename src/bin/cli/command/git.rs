@@ -0,0 +1,439 @@
+use std::collections::BTreeSet as Set;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process;
+
+use serde_json::json;
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::RawCommand;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{report_batch, Command};
+
+// Longest branch name made from the content of a task
+const MAX_BRANCH_LENGTH: usize = 50;
+
+// Runs git in the current directory, returning its output
+fn git(args: &[&str]) -> Result<String, DothisError> {
+    let output = process::Command::new("git").args(args).output()?;
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(DothisError::GitError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+// Installs a post-commit hook in the current repository, which runs
+// `dothis git post-commit` with the profile selected when installing it
+pub struct GitHookInstallCommand {
+    profile: Option<String>,
+    force: bool,
+}
+
+impl GitHookInstallCommand {
+    pub fn new(profile: Option<&str>, force: bool) -> GitHookInstallCommand {
+        GitHookInstallCommand {
+            profile: profile.map(|p| p.to_string()),
+            force: force,
+        }
+    }
+
+    // The hook runs this executable by its full path, whatever the PATH of
+    // git clients, so it has to be installed again if the executable moves.
+    // Hooks run after commits are made, which tasks cannot stop.
+    fn script(&self) -> Result<String, DothisError> {
+        let executable = std::env::current_exe()?;
+        let profile = match &self.profile {
+            Some(profile) => format!(" --profile {}", shell_quote(profile)),
+            None => String::new(),
+        };
+        Ok(format!(
+            "#!/bin/sh\n\
+             # Installed by dothis git hook install: completes the tasks the commit message\n\
+             # references, like \"closes todoist:123\" or \"#task-123\", adding it as a comment\n\
+             {}{} git post-commit || echo \"dothis: referenced tasks were not completed\" >&2\n\
+             exit 0\n",
+            shell_quote(&executable.to_string_lossy()),
+            profile
+        ))
+    }
+}
+
+impl Command for GitHookInstallCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        // Hooks live where core.hooksPath points, if set
+        let hooks = git(&["rev-parse", "--git-path", "hooks"])?;
+        let hooks = Path::new(hooks.trim());
+        let path = hooks.join("post-commit");
+        if path.exists() && !self.force {
+            return Err(DothisError::HookExists(path.to_string_lossy().to_string()));
+        }
+
+        fs::create_dir_all(hooks)?;
+        fs::write(&path, self.script()?)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        println!("{}", path.to_string_lossy());
+        Ok(())
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// Completes the tasks the message of the last commit references, adding the
+// message as a comment. Run by the post-commit hook, for commits actually made.
+pub struct GitPostCommitCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+}
+
+impl GitPostCommitCommand {
+    pub fn new(client: TodoistClient, cache: Cache, journal: Journal) -> GitPostCommitCommand {
+        GitPostCommitCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+        }
+    }
+
+    // Rebases, cherry-picks and the like replay commits whose tasks were
+    // completed when they were first made, and amended commits only complete
+    // the tasks their previous message did not reference. Repositories
+    // without a reflog only have new commits.
+    fn tasks(&self, message: &str) -> Result<Vec<u32>, DothisError> {
+        let action = git(&["reflog", "-1", "--format=%gs"])?;
+        let previous = match action.split(':').next().unwrap_or_default().trim() {
+            "" | "commit" | "commit (initial)" | "commit (merge)" => Vec::new(),
+            "commit (amend)" => task_references(&git(&["log", "-1", "--format=%B", "HEAD@{1}"])?),
+            _ => return Ok(Vec::new()),
+        };
+        Ok(task_references(message)
+            .into_iter()
+            .filter(|id| !previous.contains(id))
+            .collect())
+    }
+}
+
+impl Command for GitPostCommitCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let message = git(&["log", "-1", "--format=%B"])?.trim().to_string();
+        let tasks = self.tasks(&message)?;
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        self.cache.ensure(&self.client, "tasks", &tasks)?;
+        let mut commands = Vec::new();
+        let mut subjects = Vec::new();
+        let mut undo = Vec::new();
+        for id in tasks.iter() {
+            let name = self.cache.name("tasks", *id);
+            commands.push(RawCommand::add(
+                "note_add",
+                json!({ "item_id": id, "content": message }),
+                Uuid::new_v4(),
+            ));
+            subjects.push(format!("comment on {}", name));
+            undo.push(Vec::new());
+
+            commands.push(RawCommand::new("item_close", json!({ "id": id })));
+            subjects.push(name);
+            undo.push(Inverse::uncomplete(&self.cache, *id));
+        }
+
+        let results = self.client.sync_batch(&commands)?;
+        // Comments are deleted when completing the tasks is undone
+        let undo = results
+            .iter()
+            .zip(undo.into_iter())
+            .map(|(result, inverses)| match result {
+                Ok(Some(id)) => Inverse::delete("notes", *id),
+                _ => inverses,
+            })
+            .collect();
+        let names: Vec<String> = tasks
+            .iter()
+            .map(|id| self.cache.name("tasks", *id))
+            .collect();
+        self.journal.record_batch(
+            &format!("commit closes {}", names.join(", ")),
+            &results,
+            undo,
+        )?;
+
+        report_batch(&subjects, results)
+    }
+}
+
+// Tasks are referenced as "closes todoist:<id>" or "#task-<id>", anywhere in
+// the message
+fn task_references(message: &str) -> Vec<u32> {
+    let words: Vec<String> = message
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect();
+    let mut ids = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let reference = match word.find("#task-") {
+            Some(start) => Some(&word[start + "#task-".len()..]),
+            None if i > 0 && words[i - 1] == "closes" => word.strip_prefix("todoist:"),
+            None => None,
+        };
+        let id = reference
+            .map(|r| {
+                r.chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect::<String>()
+            })
+            .and_then(|digits| digits.parse::<u32>().ok());
+        if let Some(id) = id {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+// Creates a branch named after the content of a task, and switches to it
+pub struct GitBranchCommand {
+    cache: Cache,
+    task: u32,
+}
+
+impl GitBranchCommand {
+    pub fn new(cache: Cache, task: u32) -> GitBranchCommand {
+        GitBranchCommand {
+            cache: cache,
+            task: task,
+        }
+    }
+}
+
+impl Command for GitBranchCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let name = match self.cache.item(self.task) {
+            Some(item) => branch_name(&item.content),
+            None => String::new(),
+        };
+        let name = match name.is_empty() {
+            true => format!("task-{}", self.task),
+            false => name,
+        };
+
+        git(&["checkout", "-b", &name])?;
+        println!("{}", name);
+        Ok(())
+    }
+}
+
+// Lowercase words of the content joined with dashes, like fix-login-redirect
+fn branch_name(content: &str) -> String {
+    let mut name = String::new();
+    for c in content.chars() {
+        match c.is_alphanumeric() {
+            true => name.extend(c.to_lowercase()),
+            false if !name.is_empty() && !name.ends_with('-') => name.push('-'),
+            false => (),
+        }
+        if name.chars().count() >= MAX_BRANCH_LENGTH {
+            break;
+        }
+    }
+    name.trim_end_matches('-').to_string()
+}
+
+// Adds a task for every TODO: comment of the files tracked by git, with its
+// location as a comment. Comments that already have a task in the project,
+// by content, are skipped.
+pub struct GitTodoScanCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    project: u32,
+    paths: Vec<String>,
+}
+
+impl GitTodoScanCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        project: u32,
+        paths: Vec<&str>,
+    ) -> GitTodoScanCommand {
+        GitTodoScanCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            project: project,
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    // Locations and text of the comments, git grep fails when none match
+    fn scan(&self) -> Result<Vec<(String, String)>, DothisError> {
+        let mut args = vec![
+            "grep",
+            "-n",
+            "-I",
+            "-z",
+            "--no-color",
+            "-F",
+            "-e",
+            "TODO:",
+            "--",
+        ];
+        args.extend(self.paths.iter().map(|p| p.as_str()));
+        let output = process::Command::new("git").args(&args).output()?;
+        match output.status.code() {
+            Some(0) | Some(1) => (),
+            _ => {
+                return Err(DothisError::GitError(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ))
+            }
+        }
+
+        let output = String::from_utf8_lossy(&output.stdout);
+        let mut comments = Vec::new();
+        for line in output.lines() {
+            let mut fields = line.splitn(3, '\0');
+            if let (Some(path), Some(number), Some(text)) =
+                (fields.next(), fields.next(), fields.next())
+            {
+                if let Some(todo) = todo_text(text) {
+                    comments.push((format!("{}:{}", path, number), todo));
+                }
+            }
+        }
+        Ok(comments)
+    }
+}
+
+impl Command for GitTodoScanCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let comments = self.scan()?;
+        let response = self.client.get_resources(vec!["items", "projects"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let mut known: Set<String> = self
+            .cache
+            .items
+            .iter()
+            .filter(|item| item.project_id == self.project)
+            .map(|item| item.content.trim().to_lowercase())
+            .collect();
+        let mut commands = Vec::new();
+        let mut subjects = Vec::new();
+        let mut roots = Vec::new();
+        for (location, text) in comments.into_iter() {
+            if !known.insert(text.to_lowercase()) {
+                continue;
+            }
+            let temp_id = Uuid::new_v4();
+            commands.push(RawCommand::add(
+                "item_add",
+                json!({ "content": text, "project_id": self.project }),
+                temp_id,
+            ));
+            subjects.push(text.clone());
+            roots.push(true);
+
+            commands.push(RawCommand::add(
+                "note_add",
+                json!({ "item_id": temp_id, "content": location }),
+                Uuid::new_v4(),
+            ));
+            subjects.push(format!("note on {}", text));
+            roots.push(false);
+        }
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let results = self.client.sync_batch(&commands)?;
+        // Notes are deleted with the tasks they belong to
+        let undo = results
+            .iter()
+            .zip(roots.iter())
+            .map(|(result, root)| match (result, root) {
+                (Ok(Some(id)), true) => Inverse::delete("tasks", *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("todo-scan {} tasks", roots.iter().filter(|r| **r).count());
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&subjects, results)
+    }
+}
+
+// The text after TODO:, without the end of block comments
+fn todo_text(line: &str) -> Option<String> {
+    let start = line.find("TODO:")? + "TODO:".len();
+    let text = line[start..]
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .trim();
+    match text.is_empty() {
+        true => None,
+        false => Some(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_task_references() {
+        assert_eq!(
+            task_references("Fix login\n\nCloses todoist:4146239825, see #task-42."),
+            vec![4146239825, 42]
+        );
+        assert_eq!(task_references("(#task-42) and #TASK-42 again"), vec![42]);
+        assert_eq!(
+            task_references("todoist:7 is not closed"),
+            Vec::<u32>::new()
+        );
+        assert_eq!(task_references("#task- without an id"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn names_branches_after_tasks() {
+        assert_eq!(branch_name("Fix login redirect"), "fix-login-redirect");
+        assert_eq!(branch_name("  Review PR #12: (API) "), "review-pr-12-api");
+        assert_eq!(branch_name("Überprüfen"), "überprüfen");
+        assert_eq!(branch_name("!!!"), "");
+        assert!(branch_name(&"word ".repeat(40)).chars().count() <= MAX_BRANCH_LENGTH);
+    }
+
+    #[test]
+    fn reads_todo_comments() {
+        assert_eq!(
+            todo_text("    // TODO: retry on timeouts"),
+            Some("retry on timeouts".to_string())
+        );
+        assert_eq!(
+            todo_text("/* TODO: drop this */"),
+            Some("drop this".to_string())
+        );
+        assert_eq!(
+            todo_text("<!-- TODO: translate -->"),
+            Some("translate".to_string())
+        );
+        assert_eq!(todo_text("# TODO:"), None);
+        assert_eq!(todo_text("// todo: lowercase"), None);
+    }
+}
@@ -0,0 +1,34 @@
+use prettytable::{format, Table};
+
+use super::super::error::DothisError;
+use super::super::journal::Journal;
+use super::Command;
+
+// Lists the write commands that can be undone, numbered by the steps
+// `dothis undo --steps` takes to revert them
+pub struct HistoryCommand {
+    journal: Journal,
+    table: Table,
+}
+
+impl HistoryCommand {
+    pub fn new(journal: Journal) -> HistoryCommand {
+        HistoryCommand {
+            journal: journal,
+            table: Table::new(),
+        }
+    }
+}
+
+impl Command for HistoryCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        for (step, entry) in self.journal.entries().enumerate() {
+            self.table
+                .add_row(row![step + 1, entry.date, entry.description]);
+        }
+
+        self.table.set_format(*format::consts::FORMAT_CLEAN);
+        self.table.printstd();
+        Ok(())
+    }
+}
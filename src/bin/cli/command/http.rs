@@ -0,0 +1,114 @@
+use std::io::{Read, Write};
+
+use super::super::error::DothisError;
+
+// Seconds to wait for a request to be sent
+pub const READ_TIMEOUT: u64 = 10;
+
+// Longer headers are refused, as a client may send them endlessly
+const MAX_HEAD: usize = 16 * 1024;
+
+// A request of the local servers, read in full up to a maximum body size
+pub struct Request {
+    pub head: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    // Reads the headers, then Content-Length bytes of body. Requests with
+    // longer headers or larger bodies are refused, as None.
+    pub fn read<S: Read>(stream: &mut S, max_body: usize) -> Result<Option<Request>, DothisError> {
+        let mut request = Vec::new();
+        let mut buffer = [0; 8192];
+        let mut head_end = None;
+        while head_end.is_none() {
+            match stream.read(&mut buffer)? {
+                0 => break,
+                read => request.extend_from_slice(&buffer[..read]),
+            }
+            head_end = request.windows(4).position(|w| w == b"\r\n\r\n");
+            if head_end.map_or(request.len(), |end| end) > MAX_HEAD {
+                return Ok(None);
+            }
+        }
+        let head_end = head_end.unwrap_or(request.len());
+        let head = String::from_utf8_lossy(&request[..head_end]).to_string();
+        let length = header(&head, "Content-Length")
+            .and_then(|l| l.parse::<usize>().ok())
+            .unwrap_or(0);
+        if length > max_body {
+            return Ok(None);
+        }
+
+        let body_start = (head_end + 4).min(request.len());
+        while request.len() - body_start < length {
+            match stream.read(&mut buffer)? {
+                0 => break,
+                read => request.extend_from_slice(&buffer[..read]),
+            }
+        }
+        let body_end = (body_start + length).min(request.len());
+        Ok(Some(Request {
+            body: request[body_start..body_end].to_vec(),
+            head: head,
+        }))
+    }
+
+    pub fn method(&self) -> &str {
+        self.head.split(' ').next().unwrap_or_default()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.head, name)
+    }
+}
+
+// Header names are case insensitive
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let mut parts = line.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case(name) => Some(value.trim()),
+            _ => None,
+        }
+    })
+}
+
+pub fn respond<S: Write>(
+    stream: &mut S,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<(), DothisError> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_requests() {
+        let mut stream: &[u8] = b"POST / HTTP/1.1\r\ncontent-length: 2\r\n\r\n{}";
+        let request = Request::read(&mut stream, 2).unwrap().unwrap();
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.header("Content-Length"), Some("2"));
+        assert_eq!(request.body, b"{}");
+    }
+
+    #[test]
+    fn refuses_large_requests() {
+        let mut stream: &[u8] = b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\n{} ";
+        assert!(Request::read(&mut stream, 2).unwrap().is_none());
+        let head = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_HEAD));
+        assert!(Request::read(&mut head.as_bytes(), 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap as Map;
+
+use clap::ArgMatches;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{AddItem, CompleteItem, NewDueDate, RawCommand};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::export::taskwarrior::{self, TaskwarriorTask};
+use super::super::export::todotxt::{self, TodoTxtTask};
+use super::super::journal::{Inverse, Journal};
+use super::{read_file, report_batch, Command};
+
+// Adds the tasks of a file written by another tool. Tasks go to the given
+// project or, without one, to the first of their projects that exists, then
+// to the default project of the profile.
+pub struct ImportCommand<'a> {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    format: String,
+    args: ArgMatches<'a>,
+    default_project: Option<String>,
+}
+
+impl<'a> ImportCommand<'a> {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        format: &str,
+        args: ArgMatches<'a>,
+        default_project: Option<&str>,
+    ) -> ImportCommand<'a> {
+        ImportCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            format: format.to_string(),
+            args: args,
+            default_project: default_project.map(|p| p.to_string()),
+        }
+    }
+
+    // Names have to match exactly, ignoring case, as tags are written out in
+    // full. Those that do not are skipped with a warning instead of failing the
+    // whole import. Exports write spaces in names as underscores.
+    fn resolve_all(&mut self, resource_type: &str, names: &[String]) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for name in names.iter() {
+            let mut queries = vec![name.clone()];
+            if name.contains('_') {
+                queries.push(name.replace('_', " "));
+            }
+            let mut resolved = None;
+            for query in queries.iter() {
+                match self.cache.lookup(&self.client, resource_type, query) {
+                    Ok(found) if found.exact => {
+                        resolved = Some(found.id);
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            match resolved {
+                Some(id) => ids.push(id),
+                None => eprintln!(
+                    "warning: no {} is named \"{}\", skipped",
+                    resource_type.trim_end_matches('s'),
+                    name
+                ),
+            }
+        }
+        ids
+    }
+
+    fn todotxt_item(
+        &mut self,
+        task: &TodoTxtTask,
+        project: Option<u32>,
+        fallback: Option<u32>,
+    ) -> AddItem {
+        let labels = self.resolve_all("labels", &task.contexts);
+        let project_id = match project {
+            Some(id) => Some(id),
+            None => self
+                .resolve_all("projects", &task.projects)
+                .first()
+                .cloned()
+                .or(fallback),
+        };
+
+        AddItem {
+            project_id: project_id,
+            section_id: None,
+            parent_id: None,
+            labels: match labels.is_empty() {
+                true => None,
+                false => Some(labels),
+            },
+            content: task.content.clone(),
+            description: None,
+            due: task.due.as_deref().map(NewDueDate::from_date),
+            priority: Some(todotxt::todoist_priority(task.priority)),
+            child_order: None,
+            day_order: None,
+            collapsed: None,
+            assigned_by_uid: None,
+            responsible_uid: None,
+            auto_reminder: None,
+            auto_parse_labels: None,
+        }
+    }
+
+    fn import_todotxt(
+        &mut self,
+        contents: &str,
+        project: Option<u32>,
+        fallback: Option<u32>,
+    ) -> Result<(), DothisError> {
+        let (items, completions): (Vec<AddItem>, Vec<Option<CompleteItem>>) = contents
+            .lines()
+            .filter_map(todotxt::parse)
+            .map(|task| {
+                let completion = match task.completed {
+                    true => Some(CompleteItem {
+                        id: 0,
+                        date_completed: task
+                            .completion_date
+                            .as_ref()
+                            .map(|date| format!("{}T00:00:00Z", date)),
+                        force_history: Some(true),
+                    }),
+                    false => None,
+                };
+                (self.todotxt_item(&task, project, fallback), completion)
+            })
+            .unzip();
+
+        let results = self.client.sync_batch(&items)?;
+        let undo = results
+            .iter()
+            .map(|result| match result {
+                Ok(Some(id)) => Inverse::delete("tasks", *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("import todotxt {} tasks", items.len());
+        self.journal.record_batch(&description, &results, undo)?;
+
+        // Completed tasks are added first, then completed with their new id
+        let completions: Vec<CompleteItem> = results
+            .iter()
+            .zip(completions.into_iter())
+            .filter_map(|(result, completion)| match (result, completion) {
+                (Ok(Some(id)), Some(completion)) => Some(CompleteItem {
+                    id: *id,
+                    ..completion
+                }),
+                _ => None,
+            })
+            .collect();
+        if !completions.is_empty() {
+            let completed = self.client.sync_batch(&completions)?;
+            let subjects: Vec<String> = completions.iter().map(|c| c.id.to_string()).collect();
+            for (subject, result) in subjects.iter().zip(completed.iter()) {
+                if let Err(err) = result {
+                    eprintln!("error: could not complete {}: {}", subject, err);
+                }
+            }
+        }
+
+        let subjects: Vec<String> = items.iter().map(|i| i.content.clone()).collect();
+        report_batch(&subjects, results)
+    }
+
+    // Tasks are added in one batch, with their notes and subtasks referring
+    // to them by temp_id. A task that others depend on becomes a subtask of
+    // the first of them, and completed tasks are completed once added.
+    fn import_taskwarrior(
+        &mut self,
+        contents: &str,
+        project: Option<u32>,
+        fallback: Option<u32>,
+    ) -> Result<(), DothisError> {
+        // Deleted tasks are gone, and recurring ones are templates for the
+        // pending tasks they created, which are imported instead
+        let tasks: Vec<TaskwarriorTask> = taskwarrior::parse(contents)?
+            .into_iter()
+            .filter(|t| t.status != "deleted" && t.status != "recurring")
+            .collect();
+        let index: Map<&str, usize> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.uuid.as_str(), i))
+            .collect();
+
+        let mut parents: Map<usize, usize> = Map::new();
+        for (i, task) in tasks.iter().enumerate() {
+            for dependency in task.depends.iter() {
+                if let Some(&child) = index.get(dependency.as_str()) {
+                    if child != i {
+                        parents.entry(child).or_insert(i);
+                    }
+                }
+            }
+        }
+        // Dependency cycles cannot be subtasks, so they are cut where found
+        for i in 0..tasks.len() {
+            let mut seen = vec![i];
+            let mut current = i;
+            while let Some(&parent) = parents.get(&current) {
+                if seen.contains(&parent) {
+                    parents.remove(&current);
+                    break;
+                }
+                seen.push(parent);
+                current = parent;
+            }
+        }
+
+        // Parents are added before their subtasks
+        let mut order = Vec::with_capacity(tasks.len());
+        for i in 0..tasks.len() {
+            let mut ancestors = vec![i];
+            while let Some(parent) = parents.get(ancestors.last().unwrap_or(&i)) {
+                ancestors.push(*parent);
+            }
+            for task in ancestors.into_iter().rev() {
+                if !order.contains(&task) {
+                    order.push(task);
+                }
+            }
+        }
+
+        // Temp ids are new for every import, Todoist would reject ones reused
+        // from an earlier import of the same file
+        let temp_ids: Vec<Uuid> = tasks.iter().map(|_| Uuid::new_v4()).collect();
+        let mut commands = Vec::new();
+        let mut subjects = Vec::new();
+        let mut roots = Vec::new();
+        let mut completions = Vec::new();
+        for i in order.into_iter() {
+            let task = &tasks[i];
+            let parent_id = parents.get(&i).map(|p| temp_ids[*p]);
+            let labels = self.resolve_all("labels", &task.tags);
+            // Subtasks belong to the project of their parent
+            let project_id = match (parent_id, project) {
+                (Some(_), _) => None,
+                (None, Some(id)) => Some(id),
+                (None, None) => {
+                    let path: Vec<String> =
+                        task.project.iter().map(|p| p.replace('.', "/")).collect();
+                    self.resolve_all("projects", &path)
+                        .first()
+                        .cloned()
+                        .or(fallback)
+                }
+            };
+
+            commands.push(RawCommand::add(
+                "item_add",
+                json!({
+                    "content": task.description,
+                    "project_id": project_id,
+                    "parent_id": parent_id,
+                    "labels": match labels.is_empty() {
+                        true => Value::Null,
+                        false => json!(labels),
+                    },
+                    "due": task
+                        .due
+                        .as_deref()
+                        .and_then(taskwarrior::due_date)
+                        .map(|date| json!(NewDueDate::from_date(&date))),
+                    "priority": taskwarrior::todoist_priority(task.priority.as_deref()),
+                }),
+                temp_ids[i],
+            ));
+            subjects.push(task.description.clone());
+            roots.push(parent_id.is_none());
+
+            for annotation in task.annotations.iter() {
+                commands.push(RawCommand::add(
+                    "note_add",
+                    json!({ "item_id": temp_ids[i], "content": annotation.description }),
+                    Uuid::new_v4(),
+                ));
+                subjects.push(format!("note on {}", task.description));
+                roots.push(false);
+            }
+
+            if task.status == "completed" {
+                completions.push((
+                    RawCommand::new(
+                        "item_complete",
+                        json!({
+                            "id": temp_ids[i],
+                            "date_completed": task
+                                .end
+                                .as_deref()
+                                .and_then(taskwarrior::completion_date),
+                            "force_history": 1,
+                        }),
+                    ),
+                    format!("complete {}", task.description),
+                ));
+            }
+        }
+        // Subtasks are completed before their parents, which would complete
+        // them along with it
+        for (command, subject) in completions.into_iter().rev() {
+            commands.push(command);
+            subjects.push(subject);
+            roots.push(false);
+        }
+
+        let results = self.client.sync_batch(&commands)?;
+        // Subtasks and notes are deleted with the tasks they belong to
+        let undo = results
+            .iter()
+            .zip(roots.iter())
+            .map(|(result, root)| match (result, root) {
+                (Ok(Some(id)), true) => Inverse::delete("tasks", *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("import taskwarrior {} tasks", tasks.len());
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&subjects, results)
+    }
+}
+
+impl<'a> Command for ImportCommand<'a> {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let project = match self.args.value_of("project") {
+            Some(query) => Some(self.cache.resolve(&self.client, "projects", query)?),
+            None => None,
+        };
+        let fallback = match self.default_project.clone() {
+            Some(query) => Some(self.cache.resolve(&self.client, "projects", &query)?),
+            None => None,
+        };
+
+        let contents = read_file(self.args.value_of("file"))?;
+        match self.format.as_str() {
+            "taskwarrior" => self.import_taskwarrior(&contents, project, fallback),
+            "todotxt" => self.import_todotxt(&contents, project, fallback),
+            _ => Err(DothisError::UnknownCommand),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use clap::ArgMatches;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{AddItem, NewDueDate};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{read_lines, report_batch, resolve_arg, resolve_args, Command};
+
+// Adds a task for every line read from stdin, all with the same project,
+// section, labels, priority and due date
+pub struct ImportLinesCommand<'a> {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    args: ArgMatches<'a>,
+    default_project: Option<String>,
+}
+
+impl<'a> ImportLinesCommand<'a> {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        args: ArgMatches<'a>,
+        default_project: Option<&str>,
+    ) -> ImportLinesCommand<'a> {
+        ImportLinesCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            args: args,
+            default_project: default_project.map(|p| p.to_string()),
+        }
+    }
+
+    fn resolve_project(&mut self) -> Result<Option<u32>, DothisError> {
+        match self
+            .args
+            .value_of("project")
+            .or(self.default_project.as_deref())
+        {
+            Some(query) => Ok(Some(self.cache.resolve(&self.client, "projects", query)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a> Command for ImportLinesCommand<'a> {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let project_id = self.resolve_project()?;
+        let section_id = resolve_arg(
+            &self.client,
+            &mut self.cache,
+            &self.args,
+            "sections",
+            "section",
+        )?;
+        let labels = resolve_args(&self.client, &mut self.cache, &self.args, "labels", "label")?;
+        let priority = self
+            .args
+            .value_of("priority")
+            .map_or(None, |v| v.parse::<i32>().ok());
+
+        let lines = read_lines()?;
+        let commands: Vec<AddItem> = lines
+            .iter()
+            .map(|content| AddItem {
+                project_id: project_id,
+                section_id: section_id,
+                parent_id: None,
+                labels: labels.clone(),
+                content: content.to_string(),
+                description: None,
+                due: self.args.value_of("due").map(NewDueDate::from_string),
+                priority: priority,
+                child_order: None,
+                day_order: None,
+                collapsed: None,
+                assigned_by_uid: None,
+                responsible_uid: None,
+                auto_reminder: None,
+                auto_parse_labels: None,
+            })
+            .collect();
+        let results = self.client.sync_batch(&commands)?;
+        let undo = results
+            .iter()
+            .map(|result| match result {
+                Ok(Some(id)) => Inverse::delete("tasks", *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("import-lines {} tasks", lines.len());
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&lines, results)
+    }
+}
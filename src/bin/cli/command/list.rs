@@ -0,0 +1,316 @@
+use prettytable::{format, Cell, Row, Table};
+
+use dothis::api::client::{TodoistClient, TodoistResponse};
+use dothis::api::resource::CompletedItem;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::query::Query;
+use super::{display_date, Command};
+
+pub struct ListCommand {
+    client: TodoistClient,
+    cache: Cache,
+    resource_type: String,
+    table: Table,
+    project: Option<u32>,
+    query: Option<Query>,
+    date_format: Option<String>,
+    theme: Theme,
+    output: Output,
+    // Range of completion dates, for completed tasks
+    since: Option<String>,
+    until: Option<String>,
+    // Collaborator responsible for tasks, by email or name, or me
+    assignee: Option<String>,
+    // Completed tasks are not synced, so they are not cached either
+    completed: Vec<CompletedItem>,
+    // Ids of the listed resources, in the order of the table rows
+    ids: Vec<u32>,
+    // Whether tasks are listed with the first line of their description
+    description: bool,
+}
+
+// Tables are for people, ids for piping into bulk commands like done
+enum Output {
+    Table,
+    Ids,
+}
+
+impl ListCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        resource_type: &str,
+        table: Table,
+        project: Option<u32>,
+        query: Option<Query>,
+        date_format: Option<&str>,
+        theme: Option<&str>,
+        output: Option<&str>,
+        since: Option<String>,
+        until: Option<String>,
+        assignee: Option<&str>,
+        description: bool,
+    ) -> ListCommand {
+        ListCommand {
+            client: client,
+            cache: cache,
+            resource_type: resource_type.to_string(),
+            table: table,
+            project: project,
+            query: query,
+            date_format: date_format.map(|f| f.to_string()),
+            theme: Theme::from(theme),
+            output: match output {
+                Some("ids") => Output::Ids,
+                _ => Output::Table,
+            },
+            since: since,
+            until: until,
+            assignee: assignee.map(|a| a.to_string()),
+            completed: Vec::new(),
+            ids: Vec::new(),
+            description: description,
+        }
+    }
+
+    fn resource_types(&self) -> Result<Vec<&str>, DothisError> {
+        match self.resource_type.as_str() {
+            "tasks" => Ok(vec!["items", "projects", "collaborators", "user"]),
+            "projects" => Ok(vec!["projects"]),
+            "labels" => Ok(vec!["labels"]),
+            "notes" => Ok(vec!["notes", "projects", "items"]),
+            "completed" => Ok(vec!["projects", "user"]),
+            _ => return Err(DothisError::UnknownResource),
+        }
+    }
+
+    fn get_response(&mut self) -> Result<TodoistResponse, DothisError> {
+        let resources = self.resource_types()?;
+
+        match self.client.get_resources(resources) {
+            Ok(response) => Ok(response),
+            Err(err) => return Err(DothisError::ApiError(err)),
+        }
+    }
+
+    fn set_table_title(&mut self) -> Result<(), DothisError> {
+        let titles = match self.resource_type.as_str() {
+            "tasks" if self.description => {
+                vec![
+                    "Project",
+                    "Added",
+                    "Due",
+                    "Assignee",
+                    "Content",
+                    "Description",
+                ]
+            }
+            "tasks" => vec!["Project", "Added", "Due", "Assignee", "Content"],
+            "projects" => vec!["Project", "Parent"],
+            "notes" => vec!["Project", "Task", "Content"],
+            "labels" => vec!["Name", "Favorite", "Deleted"],
+            "completed" => vec!["Project", "Completed", "Content"],
+            other => return Err(DothisError::UnknownResource),
+        };
+        let style = self.theme.title_style();
+        self.table.set_titles(Row::new(
+            titles
+                .into_iter()
+                .map(|t| Cell::new(t).style_spec(style))
+                .collect(),
+        ));
+
+        Ok(())
+    }
+
+    fn format_date(&self, date: &str) -> String {
+        display_date(date, self.date_format.as_deref(), self.cache.user.as_ref())
+    }
+
+    // The user is synced along with tasks, without it no task is theirs
+    fn assignee(&self) -> Result<Option<u32>, DothisError> {
+        match self.assignee.as_deref() {
+            Some("me") => Ok(Some(self.cache.user.as_ref().map_or(0, |u| u.id))),
+            Some(query) => Ok(Some(self.cache.resolver().collaborator(query)?.id)),
+            None => Ok(None),
+        }
+    }
+
+    // Rows are built from the cache, which was just updated with the response
+    fn set_table_rows(&mut self) -> Result<(), DothisError> {
+        match self.resource_type.as_str() {
+            "tasks" => {
+                let assignee = self.assignee()?;
+                for project in self.cache.projects.iter() {
+                    if self.project.map_or(false, |id| id != project.id) {
+                        continue;
+                    }
+                    let query = &self.query;
+                    for task in self.cache.items.iter().filter(|t| {
+                        t.project_id == project.id
+                            && query.as_ref().map_or(true, |q| q.matches(t))
+                            && assignee.map_or(true, |a| t.responsible_uid == Some(a as i32))
+                    }) {
+                        self.ids.push(task.id);
+                        let added = self.format_date(&task.date_added);
+                        let responsible = task
+                            .responsible_uid
+                            .map_or(String::new(), |uid| self.cache.user_name(uid as u32));
+                        let mut row = row![
+                            project.name,
+                            added,
+                            task.due.as_ref().map_or("", |d| &d.string),
+                            responsible,
+                            task.content
+                        ];
+                        if self.description {
+                            row.add_cell(Cell::new(
+                                task.description.lines().next().unwrap_or_default(),
+                            ));
+                        }
+                        self.table.add_row(row);
+                    }
+                }
+            }
+            "projects" => {
+                for project in self.cache.projects.iter() {
+                    self.ids.push(project.id);
+                    match project.parent_id {
+                        Some(parent_id) => self.table.add_row(row![
+                            project.name,
+                            self.cache.project(parent_id).map_or("", |p| &p.name)
+                        ]),
+                        None => self.table.add_row(row![project.name, ""]),
+                    };
+                }
+            }
+            "notes" => {
+                for note in self.cache.notes.iter() {
+                    if self.project.map_or(false, |id| id != note.project_id) {
+                        continue;
+                    }
+                    self.ids.push(note.id);
+                    self.table.add_row(row![
+                        self.cache.project(note.project_id).map_or("", |p| &p.name),
+                        self.cache.item(note.item_id).map_or("", |i| &i.content),
+                        note.content
+                    ]);
+                }
+            }
+            "labels" => {
+                for label in self.cache.labels.iter() {
+                    self.ids.push(label.id);
+                    self.table
+                        .add_row(row![label.name, label.is_favorite, label.is_deleted]);
+                }
+            }
+            // Completed ones list the id of the task, not of the completion
+            "completed" => {
+                for task in self.completed.iter() {
+                    self.ids.push(task.task_id);
+                    self.table.add_row(row![
+                        self.cache.project(task.project_id).map_or("", |p| &p.name),
+                        self.format_date(&task.completed_date),
+                        task.content
+                    ]);
+                }
+            }
+            other => return Err(DothisError::UnknownResource),
+        };
+
+        Ok(())
+    }
+
+    fn format_table(&mut self) {
+        let tbl_format = format::FormatBuilder::new()
+            .column_separator(' ')
+            .borders(' ')
+            .separators(
+                &[format::LinePosition::Title],
+                format::LineSeparator::new('=', ' ', ' ', ' '),
+            )
+            .build();
+        self.table.set_format(tbl_format);
+
+        let style = self.theme.first_column_style();
+        if !style.is_empty() {
+            for row in self.table.row_iter_mut() {
+                if let Some(cell) = row.get_mut_cell(0) {
+                    *cell = Cell::new(&cell.get_content()).style_spec(style);
+                }
+            }
+        }
+    }
+
+    fn build_table(&mut self) -> Result<(), DothisError> {
+        self.set_table_title()?;
+        self.set_table_rows()?;
+
+        match self.output {
+            Output::Table => {
+                self.format_table();
+                self.table.printstd();
+            }
+            Output::Ids => {
+                for id in self.ids.iter() {
+                    println!("{}", id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Color themes for table output, configured per profile with color_theme
+enum Theme {
+    Plain,
+    Default,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn from(name: Option<&str>) -> Theme {
+        match name {
+            Some("none") | Some("plain") => Theme::Plain,
+            Some("dark") => Theme::Dark,
+            Some("light") => Theme::Light,
+            _ => Theme::Default,
+        }
+    }
+
+    fn title_style(&self) -> &str {
+        match self {
+            Theme::Plain => "",
+            Theme::Default => "b",
+            Theme::Dark => "bFc",
+            Theme::Light => "bFb",
+        }
+    }
+
+    fn first_column_style(&self) -> &str {
+        match self {
+            Theme::Plain | Theme::Default => "",
+            Theme::Dark => "Fy",
+            Theme::Light => "Fm",
+        }
+    }
+}
+
+impl Command for ListCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self.get_response()?;
+        self.cache.update(response);
+        self.cache.save()?;
+        if self.resource_type == "completed" {
+            self.completed = self.client.get_completed(
+                self.project,
+                self.since.as_deref(),
+                self.until.as_deref(),
+            )?;
+        }
+        self.build_table()
+    }
+}
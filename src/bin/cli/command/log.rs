@@ -0,0 +1,160 @@
+use std::thread;
+use std::time::Duration;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{ActivityEvent, ActivityQuery, EventType, ObjectType};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::{display_date, Command};
+
+// Following polls the activity log this often
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(30);
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+// Prints the events of the activity log, oldest first: the current week,
+// or every week since the given time
+pub struct LogCommand {
+    client: TodoistClient,
+    cache: Cache,
+    query: ActivityQuery,
+    since: Option<String>,
+    follow: bool,
+    date_format: Option<String>,
+}
+
+impl LogCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        query: ActivityQuery,
+        since: Option<String>,
+        follow: bool,
+        date_format: Option<&str>,
+    ) -> LogCommand {
+        LogCommand {
+            client: client,
+            cache: cache,
+            query: query,
+            since: since,
+            follow: follow,
+            date_format: date_format.map(|f| f.to_string()),
+        }
+    }
+
+    fn fetch(&mut self, page: u32) -> Result<Vec<ActivityEvent>, DothisError> {
+        self.query.page = Some(page);
+        Ok(self.client.get_activity(&self.query)?)
+    }
+
+    // Pages go back a week each, so there are as many as weeks since then
+    fn pages(&self) -> u32 {
+        let since = self
+            .since
+            .as_ref()
+            .and_then(|since| time::strptime(since, "%Y-%m-%dT%H:%M").ok())
+            .map(|tm| tm.to_timespec().sec);
+        match since {
+            Some(since) => ((time::get_time().sec - since).max(0) / WEEK) as u32 + 1,
+            None => 1,
+        }
+    }
+
+    fn print(&self, event: &ActivityEvent) {
+        let date = display_date(
+            &event.event_date,
+            self.date_format.as_deref(),
+            self.cache.user.as_ref(),
+        );
+        let who = match event.initiator_id {
+            Some(id) => self.cache.user_name(id),
+            None => "you".to_string(),
+        };
+        let object = match event.object_type {
+            ObjectType::Item => "task",
+            ObjectType::Note => "comment",
+            ObjectType::Project => "project",
+            ObjectType::Other => "object",
+        };
+        let content = event
+            .content()
+            .map_or(event.object_id.to_string(), |c| format!("\"{}\"", c));
+
+        let mut line = format!(
+            "{}  {}  {} {} {}",
+            date,
+            who,
+            event_name(event.event_type),
+            object,
+            content
+        );
+        // Updates keep what was changed
+        let previous = event
+            .extra_data
+            .get("last_content")
+            .or(event.extra_data.get("last_name"))
+            .and_then(|c| c.as_str());
+        if let Some(previous) = previous {
+            line.push_str(&format!(", was \"{}\"", previous));
+        }
+        let project = match event.object_type {
+            ObjectType::Project => None,
+            _ => event
+                .parent_project_id
+                .and_then(|id| self.cache.project(id)),
+        };
+        if let Some(project) = project {
+            line.push_str(&format!(" in {}", project.name));
+        }
+        println!("{}", line);
+    }
+}
+
+fn event_name(event_type: EventType) -> &'static str {
+    match event_type {
+        EventType::Added => "added",
+        EventType::Updated => "updated",
+        EventType::Deleted => "deleted",
+        EventType::Completed => "completed",
+        EventType::Uncompleted => "uncompleted",
+        EventType::Archived => "archived",
+        EventType::Unarchived => "unarchived",
+        EventType::Shared => "shared",
+        EventType::Left => "left",
+        EventType::Other => "changed",
+    }
+}
+
+impl Command for LogCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self
+            .client
+            .get_resources(vec!["projects", "collaborators", "user"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let mut events = Vec::new();
+        for page in 0..self.pages() {
+            events.extend(self.fetch(page)?);
+        }
+        // Dates of events and since are both UTC, and compare as strings
+        if let Some(since) = &self.since {
+            events.retain(|e| e.event_date.as_str() >= since.as_str());
+        }
+        for event in events.iter().rev() {
+            self.print(event);
+        }
+
+        // Event ids grow, so new events are those with a greater id
+        let mut last = events.iter().map(|e| e.id).max().unwrap_or(0);
+        while self.follow {
+            thread::sleep(FOLLOW_INTERVAL);
+            let events = self.fetch(0)?;
+            for event in events.iter().rev().filter(|e| e.id > last) {
+                self.print(event);
+            }
+            last = events.iter().map(|e| e.id).max().unwrap_or(0).max(last);
+        }
+        Ok(())
+    }
+}
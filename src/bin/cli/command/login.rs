@@ -0,0 +1,32 @@
+use super::super::credentials;
+use super::super::error::DothisError;
+use super::Command;
+
+pub struct LoginCommand {
+    profile: String,
+}
+
+impl LoginCommand {
+    pub fn new(profile: &str) -> LoginCommand {
+        LoginCommand {
+            profile: profile.to_string(),
+        }
+    }
+}
+
+impl Command for LoginCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let token = credentials::read_secret("Todoist API token: ")?;
+        if token.is_empty() {
+            return Err(DothisError::MissingToken);
+        }
+
+        let path = credentials::store_token(&self.profile, &token)?;
+        eprintln!(
+            "token for profile {} stored in {}",
+            self.profile,
+            path.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::time::Duration;
+
+use clap::ArgMatches;
+
+use dothis::api::client::{CommandResult, TodoistClient, TodoistResponse};
+use dothis::api::resource::User;
+
+use super::cache::Cache;
+use super::error::DothisError;
+
+mod add;
+mod assign;
+mod backup;
+mod completions;
+mod config;
+mod daemon;
+mod done;
+mod edit;
+mod editor;
+mod export;
+mod git;
+mod history;
+mod http;
+mod import;
+mod import_lines;
+mod list;
+mod log;
+mod login;
+mod notifications;
+mod remove;
+mod reschedule;
+mod restore;
+mod serve_ical;
+mod share;
+mod show;
+mod stats;
+mod template;
+mod undo;
+mod watch;
+mod webhook;
+
+pub use self::add::AddCommand;
+pub use self::assign::AssignCommand;
+pub use self::backup::BackupCommand;
+pub use self::completions::{CompleteCommand, CompletionsCommand};
+pub use self::config::ConfigCommand;
+pub use self::daemon::{DaemonCommand, Listen};
+pub use self::done::DoneCommand;
+pub use self::edit::EditCommand;
+pub use self::export::ExportCommand;
+pub use self::git::{
+    GitBranchCommand, GitHookInstallCommand, GitPostCommitCommand, GitTodoScanCommand,
+};
+pub use self::history::HistoryCommand;
+pub use self::import::ImportCommand;
+pub use self::import_lines::ImportLinesCommand;
+pub use self::list::ListCommand;
+pub use self::log::LogCommand;
+pub use self::login::LoginCommand;
+pub use self::notifications::{
+    AnswerInvitationCommand, NotificationsCommand, ReadNotificationsCommand,
+};
+pub use self::remove::RemoveCommand;
+pub use self::reschedule::RescheduleCommand;
+pub use self::restore::RestoreCommand;
+pub use self::serve_ical::ServeIcalCommand;
+pub use self::share::ShareCommand;
+pub use self::show::ShowCommand;
+pub use self::stats::StatsCommand;
+pub use self::template::{ApplyTemplateCommand, ExportTemplateCommand};
+pub use self::undo::UndoCommand;
+pub use self::watch::WatchCommand;
+pub use self::webhook::{WebhookServeCommand, WebhookSignCommand};
+
+pub trait Command {
+    // list command should eventually support other outputs besides stdout via an argument
+    fn execute(&mut self) -> Result<(), DothisError>;
+}
+
+pub enum Commands {
+    ListCommand,
+    AddCommand,
+    EditCommand,
+    DoneCommand,
+    RemoveCommand,
+    ShowCommand,
+    ConfigCommand,
+    LoginCommand,
+    CompletionsCommand,
+    RescheduleCommand,
+    ImportLinesCommand,
+    UndoCommand,
+    HistoryCommand,
+    ExportCommand,
+    ServeIcalCommand,
+    ImportCommand,
+    ExportTemplateCommand,
+    ApplyTemplateCommand,
+    BackupCommand,
+    RestoreCommand,
+    StatsCommand,
+    LogCommand,
+    AssignCommand,
+    ShareCommand,
+    NotificationsCommand,
+    ReadNotificationsCommand,
+    AnswerInvitationCommand,
+    WatchCommand,
+    WebhookServeCommand,
+    WebhookSignCommand,
+    DaemonCommand,
+    GitHookInstallCommand,
+    GitPostCommitCommand,
+    GitBranchCommand,
+    GitTodoScanCommand,
+}
+
+// Arguments holding ids are validated when parsed by clap
+pub fn id_of(args: &ArgMatches, name: &str) -> Option<u32> {
+    args.value_of(name).map_or(None, |v| v.parse::<u32>().ok())
+}
+
+// Resolves the argument, given as an id or name, to the id of a resource of the given type
+pub fn resolve_arg(
+    client: &TodoistClient,
+    cache: &mut Cache,
+    args: &ArgMatches,
+    resource_type: &str,
+    name: &str,
+) -> Result<Option<u32>, DothisError> {
+    match args.value_of(name) {
+        Some(query) => Ok(Some(cache.resolve(client, resource_type, query)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn resolve_args(
+    client: &TodoistClient,
+    cache: &mut Cache,
+    args: &ArgMatches,
+    resource_type: &str,
+    name: &str,
+) -> Result<Option<Vec<u32>>, DothisError> {
+    let mut ids = Vec::new();
+    match args.values_of(name) {
+        Some(values) => {
+            for query in values {
+                ids.push(cache.resolve(client, resource_type, query)?);
+            }
+            Ok(Some(ids))
+        }
+        None => Ok(None),
+    }
+}
+
+// Bulk commands read from stdin, one value per line, skipping blank lines
+pub fn read_lines() -> Result<Vec<String>, DothisError> {
+    let mut lines = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line.trim().to_string());
+        }
+    }
+    Ok(lines)
+}
+
+// Files named - are read from stdin
+pub fn read_file(path: Option<&str>) -> Result<String, DothisError> {
+    match path {
+        Some("-") | None => {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            Ok(contents)
+        }
+        Some(path) => Ok(fs::read_to_string(path)?),
+    }
+}
+
+// Descriptions are given as an argument or read from a file, without the
+// trailing newline of files
+pub fn description_of(args: &ArgMatches) -> Result<Option<String>, DothisError> {
+    match (
+        args.value_of("description"),
+        args.value_of("description-file"),
+    ) {
+        (Some(_), Some(_)) => Err(DothisError::DescriptionConflict),
+        (Some(description), None) => Ok(Some(description.to_string())),
+        (None, Some(path)) => Ok(Some(read_file(Some(path))?.trim_end().to_string())),
+        (None, None) => Ok(None),
+    }
+}
+
+// Prints the id of every resource a bulk command succeeded for, one per line
+// so they can be piped to another command, and the error of the ones that
+// failed. Subjects identify each command: the resource id, or what was added.
+pub fn report_batch(subjects: &[String], results: Vec<CommandResult>) -> Result<(), DothisError> {
+    let total = results.len();
+    let mut failed = 0;
+    for (subject, result) in subjects.iter().zip(results.into_iter()) {
+        match result {
+            Ok(Some(new_id)) => println!("{}", new_id),
+            Ok(None) => println!("{}", subject),
+            Err(err) => {
+                failed += 1;
+                eprintln!("error: {}: {}", subject, err);
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(DothisError::BatchError(failed, total)),
+    }
+}
+
+// Fails with the errors reported by the API for any of the commands sent
+pub fn check_response(response: &TodoistResponse) -> Result<(), DothisError> {
+    let errors: Vec<&str> = response.errors().into_iter().map(|(_, e)| e).collect();
+    match errors.len() {
+        0 => Ok(()),
+        _ => Err(DothisError::SyncError(errors.join(", "))),
+    }
+}
+
+// Dates given as arguments are local days or times, like 2020-01-31 or
+// 2020-01-31T09:00, the API expects UTC times
+pub fn api_date(date: &str) -> Option<String> {
+    let mut tm = time::strptime(date, "%Y-%m-%dT%H:%M")
+        .or_else(|_| time::strptime(date, "%Y-%m-%d"))
+        .ok()?;
+    tm.tm_utcoff = time::now().tm_utcoff;
+    tm.tm_isdst = -1;
+    time::strftime("%Y-%m-%dT%H:%M", &time::at_utc(tm.to_timespec())).ok()
+}
+
+// Intervals are given in seconds, or with a unit like 30s, 5m or 1h
+pub fn duration_of(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return None,
+    };
+    match number.parse::<u64>() {
+        Ok(number) if number > 0 => number.checked_mul(seconds).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+// Todoist returns dates either as full days or RFC 3339 timestamps, with or without UTC offset.
+// UTC timestamps are shown in the timezone of the user, when known.
+// Dates that cannot be parsed are returned unchanged
+pub fn format_date(date: &str, fmt: &str, user: Option<&User>) -> String {
+    if let (Some(user), Ok(tm)) = (user, time::strptime(date, "%Y-%m-%dT%H:%M:%SZ")) {
+        return time::strftime(fmt, &user_time(user, tm.to_timespec()))
+            .unwrap_or_else(|_| date.to_string());
+    }
+    let formats = ["%Y-%m-%dT%H:%M:%SZ", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d"];
+    for input_format in formats.iter() {
+        if let Ok(tm) = time::strptime(date, input_format) {
+            if let Ok(formatted) = time::strftime(fmt, &tm) {
+                return formatted;
+            }
+        }
+    }
+    date.to_string()
+}
+
+// Dates are shown with the profile date_format, or else the way the user
+// chose in Todoist, without a time for full days
+pub fn display_date(date: &str, date_format: Option<&str>, user: Option<&User>) -> String {
+    match (date_format, user) {
+        (Some(fmt), _) => format_date(date, fmt, user),
+        (None, Some(user)) if date.len() == 10 => format_date(date, user.day_format(), Some(user)),
+        (None, Some(user)) => format_date(date, &user.datetime_format(), Some(user)),
+        (None, None) => date.to_string(),
+    }
+}
+
+// The time in the timezone of the user, wherever dothis runs
+fn user_time(user: &User, utc: time::Timespec) -> time::Tm {
+    let offset = user.utc_offset();
+    let mut tm = time::at_utc(utc + time::Duration::seconds(offset));
+    tm.tm_utcoff = offset as i32;
+    tm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(duration_of("90"), Some(Duration::from_secs(90)));
+        assert_eq!(duration_of("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(duration_of("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(duration_of("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(duration_of("0"), None);
+        assert_eq!(duration_of("5d"), None);
+        assert_eq!(duration_of("m"), None);
+        assert_eq!(duration_of("-5m"), None);
+        assert_eq!(duration_of(&format!("{}h", u64::MAX / 60)), None);
+    }
+
+    #[test]
+    fn converts_dates_to_utc_times() {
+        let day = api_date("2020-01-31");
+        assert!(day.is_some());
+        assert_eq!(day, api_date("2020-01-31T00:00"));
+        assert_eq!(api_date("2020-01-31T09:00").map(|d| d.len()), Some(16));
+        assert_eq!(api_date("31/01/2020"), None);
+        assert_eq!(api_date("tomorrow"), None);
+    }
+}
@@ -0,0 +1,251 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{
+    AcceptInvitation, LiveNotification, MarkAllNotificationsRead, MarkNotificationRead,
+    NotificationType, RejectInvitation,
+};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::{check_response, display_date, report_batch, Command};
+
+// Lists notifications, most recent first. Those that arrived since the last
+// listing are marked new, others still unread are marked unread.
+pub struct NotificationsCommand {
+    client: TodoistClient,
+    cache: Cache,
+    unread: bool,
+    date_format: Option<String>,
+}
+
+impl NotificationsCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        unread: bool,
+        date_format: Option<&str>,
+    ) -> NotificationsCommand {
+        NotificationsCommand {
+            client: client,
+            cache: cache,
+            unread: unread,
+            date_format: date_format.map(|f| f.to_string()),
+        }
+    }
+
+    fn describe(&self, notification: &LiveNotification) -> String {
+        let from = match (&notification.from_user, notification.from_uid) {
+            (Some(user), _) => user.full_name.clone(),
+            (None, Some(uid)) => self.cache.user_name(uid),
+            (None, None) => "someone".to_string(),
+        };
+        let project = notification
+            .project_name
+            .clone()
+            .or_else(|| {
+                notification
+                    .project_id
+                    .map(|id| self.cache.name("projects", id))
+            })
+            .unwrap_or_default();
+        let task = notification
+            .item_content
+            .clone()
+            .or_else(|| notification.item_id.map(|id| self.cache.name("tasks", id)))
+            .unwrap_or_default();
+
+        match notification.notification_type {
+            NotificationType::ShareInvitationSent => match is_pending(notification) {
+                true => format!(
+                    "{} invited you to {}, answer with notifications accept or reject",
+                    from, project
+                ),
+                false => format!("{} invited you to {}", from, project),
+            },
+            NotificationType::ShareInvitationAccepted => {
+                format!("{} accepted your invitation to {}", from, project)
+            }
+            NotificationType::ShareInvitationRejected => {
+                format!("{} declined your invitation to {}", from, project)
+            }
+            NotificationType::UserLeftProject => format!("{} left {}", from, project),
+            NotificationType::UserRemovedFromProject => format!(
+                "{} was removed from {}",
+                notification.removed_name.as_deref().unwrap_or("someone"),
+                project
+            ),
+            NotificationType::ItemAssigned => {
+                let responsible = notification
+                    .responsible_uid
+                    .map_or("you".to_string(), |uid| self.cache.user_name(uid));
+                format!("{} assigned \"{}\" to {}", from, task, responsible)
+            }
+            NotificationType::ItemCompleted => format!("{} completed \"{}\"", from, task),
+            NotificationType::ItemUncompleted => format!("{} uncompleted \"{}\"", from, task),
+            NotificationType::NoteAdded => match &notification.note_content {
+                Some(content) => format!("{} commented on \"{}\": {}", from, task, content),
+                None => format!("{} commented on \"{}\"", from, task),
+            },
+            NotificationType::Other => match &notification.notification_key {
+                Some(key) => format!("notification from {}: {}", from, key),
+                None => format!("notification from {}", from),
+            },
+        }
+    }
+}
+
+impl Command for NotificationsCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self.client.get_resources(vec![
+            "live_notifications",
+            "collaborators",
+            "user",
+            "projects",
+        ])?;
+        let seen = self.cache.notifications_seen;
+        self.cache.update(response);
+        self.cache.notifications_seen = self
+            .cache
+            .live_notifications
+            .iter()
+            .map(|n| n.seq_no)
+            .max()
+            .unwrap_or(0)
+            .max(seen);
+        self.cache.save()?;
+
+        let mut notifications: Vec<&LiveNotification> = self
+            .cache
+            .live_notifications
+            .iter()
+            .filter(|n| !n.is_deleted && (n.is_unread || !self.unread))
+            .collect();
+        notifications.sort_by(|a, b| b.created.cmp(&a.created));
+
+        for notification in notifications.into_iter() {
+            let mark = match (notification.seq_no > seen, notification.is_unread) {
+                (true, _) => "new",
+                (false, true) => "unread",
+                (false, false) => "",
+            };
+            let created = time::strftime(
+                "%Y-%m-%dT%H:%M:%SZ",
+                &time::at_utc(time::Timespec::new(notification.created, 0)),
+            )
+            .unwrap_or_default();
+            println!(
+                "{:6} {:>10}  {}  {}",
+                mark,
+                notification.id,
+                display_date(
+                    &created,
+                    self.date_format.as_deref(),
+                    self.cache.user.as_ref()
+                ),
+                self.describe(notification)
+            );
+        }
+        Ok(())
+    }
+}
+
+// Invitations still waiting for an answer have no state, or invited
+fn is_pending(notification: &LiveNotification) -> bool {
+    notification.notification_type == NotificationType::ShareInvitationSent
+        && notification
+            .state
+            .as_deref()
+            .map_or(true, |state| state == "invited")
+}
+
+// Marks notifications as read, every one of them without ids
+pub struct ReadNotificationsCommand {
+    client: TodoistClient,
+    ids: Vec<u32>,
+}
+
+impl ReadNotificationsCommand {
+    pub fn new(client: TodoistClient, ids: Vec<u32>) -> ReadNotificationsCommand {
+        ReadNotificationsCommand {
+            client: client,
+            ids: ids,
+        }
+    }
+}
+
+impl Command for ReadNotificationsCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        if self.ids.is_empty() {
+            let response = self
+                .client
+                .sync_resources(vec![MarkAllNotificationsRead {}])?;
+            return check_response(&response);
+        }
+
+        let commands: Vec<MarkNotificationRead> = self
+            .ids
+            .iter()
+            .map(|id| MarkNotificationRead { id: *id })
+            .collect();
+        let results = self.client.sync_batch(&commands)?;
+        let subjects: Vec<String> = self.ids.iter().map(|id| id.to_string()).collect();
+        report_batch(&subjects, results)
+    }
+}
+
+// Accepts or rejects the share invitation of a notification
+pub struct AnswerInvitationCommand {
+    client: TodoistClient,
+    cache: Cache,
+    id: u32,
+    accept: bool,
+}
+
+impl AnswerInvitationCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        id: u32,
+        accept: bool,
+    ) -> AnswerInvitationCommand {
+        AnswerInvitationCommand {
+            client: client,
+            cache: cache,
+            id: id,
+            accept: accept,
+        }
+    }
+}
+
+impl Command for AnswerInvitationCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self.client.get_resources(vec!["live_notifications"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let invitation = self
+            .cache
+            .live_notifications
+            .iter()
+            .find(|n| n.id == self.id && is_pending(n))
+            .and_then(|n| match (n.invitation_id, &n.invitation_secret) {
+                (Some(id), Some(secret)) => Some((id, secret.clone())),
+                _ => None,
+            });
+        let (invitation_id, invitation_secret) = match invitation {
+            Some(invitation) => invitation,
+            None => return Err(DothisError::NotInvitation(self.id)),
+        };
+
+        let response = match self.accept {
+            true => self.client.sync_resources(vec![AcceptInvitation {
+                invitation_id: invitation_id,
+                invitation_secret: invitation_secret,
+            }])?,
+            false => self.client.sync_resources(vec![RejectInvitation {
+                invitation_id: invitation_id,
+                invitation_secret: invitation_secret,
+            }])?,
+        };
+        check_response(&response)
+    }
+}
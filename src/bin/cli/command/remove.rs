@@ -0,0 +1,57 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{DeleteItem, DeleteLabel, DeleteNote, DeleteProject};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{check_response, Command};
+
+pub struct RemoveCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    resource_type: String,
+    id: u32,
+}
+
+impl RemoveCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        resource_type: &str,
+        id: u32,
+    ) -> RemoveCommand {
+        RemoveCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            resource_type: resource_type.to_string(),
+            id: id,
+        }
+    }
+}
+
+impl Command for RemoveCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let id = self.id;
+        self.cache
+            .ensure(&self.client, &self.resource_type, &[id])?;
+        let undo = Inverse::readd(&self.cache, &self.resource_type, id);
+        let description = format!(
+            "rm {} {}",
+            self.resource_type.trim_end_matches('s'),
+            self.cache.name(&self.resource_type, id)
+        );
+
+        let response = match self.resource_type.as_str() {
+            "tasks" => self.client.sync_resources(vec![DeleteItem { id: id }])?,
+            "projects" => self.client.sync_resources(vec![DeleteProject { id: id }])?,
+            "labels" => self.client.sync_resources(vec![DeleteLabel { id: id }])?,
+            "notes" => self.client.sync_resources(vec![DeleteNote { id: id }])?,
+            _ => return Err(DothisError::UnknownResource),
+        };
+        check_response(&response)?;
+        self.journal.record(&description, undo)
+    }
+}
@@ -0,0 +1,65 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{NewDueDate, UpdateItem};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{report_batch, Command};
+
+pub struct RescheduleCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    tasks: Vec<u32>,
+    due: String,
+}
+
+impl RescheduleCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        tasks: Vec<u32>,
+        due: &str,
+    ) -> RescheduleCommand {
+        RescheduleCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            tasks: tasks,
+            due: due.to_string(),
+        }
+    }
+}
+
+impl Command for RescheduleCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        self.cache.ensure(&self.client, "tasks", &self.tasks)?;
+        let undo = self
+            .tasks
+            .iter()
+            .map(|id| Inverse::reschedule(&self.cache, *id))
+            .collect();
+        let names: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|id| self.cache.name("tasks", *id))
+            .collect();
+
+        let commands: Vec<UpdateItem> = self
+            .tasks
+            .iter()
+            .map(|id| UpdateItem {
+                id: *id,
+                due: Some(NewDueDate::from_string(&self.due)),
+                ..UpdateItem::default()
+            })
+            .collect();
+        let results = self.client.sync_batch(&commands)?;
+        let description = format!("reschedule to {}: {}", self.due, names.join(", "));
+        self.journal.record_batch(&description, &results, undo)?;
+
+        let subjects: Vec<String> = self.tasks.iter().map(|id| id.to_string()).collect();
+        report_batch(&subjects, results)
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::BTreeMap as Map;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{CompletedItem, ProjectNote, RawCommand};
+
+use super::super::archive::Archive;
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::journal::{Inverse, Journal};
+use super::{report_batch, Command};
+
+// Adds back projects of an archive, with their comments, sections, tasks,
+// notes and subprojects, and the labels they use that are missing from the
+// account. Archived projects are archived again once restored, and completed
+// tasks are completed again, outside of sections. Restored resources get new
+// ids, like those undo adds back.
+pub struct RestoreCommand {
+    client: TodoistClient,
+    cache: Cache,
+    journal: Journal,
+    archive: Cache,
+    project_notes: Vec<ProjectNote>,
+    completed: Vec<CompletedItem>,
+    date: String,
+    projects: Vec<String>,
+}
+
+impl RestoreCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        journal: Journal,
+        mut archive: Archive,
+        projects: Vec<String>,
+    ) -> RestoreCommand {
+        let mut project_notes = archive.resources.project_notes.take().unwrap_or_default();
+        let mut resources = Cache::default();
+        resources.update(archive.resources);
+        // Archived projects are left out of syncs, so they and their
+        // resources are found apart
+        for project in archive.archived_projects.into_iter() {
+            if resources.project(project.id).is_none() {
+                resources.projects.push(project);
+            }
+        }
+        for data in archive.archived_data.into_iter() {
+            for section in data.sections.into_iter() {
+                if resources.section(section.id).is_none() {
+                    resources.sections.push(section);
+                }
+            }
+            for item in data.items.into_iter() {
+                if resources.item(item.id).is_none() {
+                    resources.items.push(item);
+                }
+            }
+            project_notes.extend(data.project_notes);
+        }
+        RestoreCommand {
+            client: client,
+            cache: cache,
+            journal: journal,
+            archive: resources,
+            project_notes: project_notes,
+            completed: archive.completed,
+            date: archive.date,
+            projects: projects,
+        }
+    }
+
+    // Projects are given by name or path as they were in the archive.
+    // Without any, every project is restored. Projects under another one
+    // being restored are restored with it.
+    fn selected(&self) -> Result<Vec<u32>, DothisError> {
+        let selected: Vec<u32> = match self.projects.is_empty() {
+            true => self
+                .archive
+                .projects
+                .iter()
+                .filter(|p| p.parent_id.is_none() && !p.is_deleted)
+                .map(|p| p.id)
+                .collect(),
+            false => {
+                let resolver = self.archive.resolver();
+                self.projects
+                    .iter()
+                    .map(|query| resolver.project(query).map(|found| found.id))
+                    .collect::<Result<Vec<u32>, _>>()?
+            }
+        };
+
+        Ok(selected
+            .iter()
+            .filter(|id| {
+                let mut parent = self.archive.project(**id).and_then(|p| p.parent_id);
+                let mut depth = 0;
+                while let Some(parent_id) = parent {
+                    if selected.contains(&parent_id) || depth > self.archive.projects.len() {
+                        return false;
+                    }
+                    parent = self.archive.project(parent_id).and_then(|p| p.parent_id);
+                    depth += 1;
+                }
+                true
+            })
+            .cloned()
+            .collect())
+    }
+
+    // The project and its subprojects, in the order they are added back
+    fn subprojects(&self, id: u32) -> Vec<u32> {
+        let mut ids = vec![id];
+        for child in self
+            .archive
+            .projects
+            .iter()
+            .filter(|p| p.parent_id == Some(id))
+        {
+            ids.extend(self.subprojects(child.id));
+        }
+        ids
+    }
+
+    // Completed tasks are added to the projects they were restored to, then
+    // completed at the time they were
+    fn completed_commands(
+        &self,
+        projects: &Map<u32, Value>,
+        commands: &mut Vec<(RawCommand, Option<&'static str>)>,
+    ) {
+        let mut completions = Vec::new();
+        for completed in self.completed.iter() {
+            let project_id = match projects.get(&completed.project_id) {
+                Some(project_id) => project_id,
+                None => continue,
+            };
+            let temp_id = Uuid::new_v4();
+            commands.push((
+                RawCommand::add(
+                    "item_add",
+                    json!({ "content": completed.content, "project_id": project_id }),
+                    temp_id,
+                ),
+                // Only tasks restored to the inbox are deleted on their own
+                // to undo, the others go with their projects
+                match project_id.is_u64() {
+                    true => Some("tasks"),
+                    false => None,
+                },
+            ));
+            completions.push((
+                RawCommand::new(
+                    "item_complete",
+                    json!({
+                        "id": temp_id,
+                        "date_completed": completed.completed_date,
+                        "force_history": 1,
+                    }),
+                ),
+                None,
+            ));
+        }
+        commands.extend(completions);
+    }
+
+    // Comments of projects are added to the projects they were restored to
+    fn project_note_commands(
+        &self,
+        projects: &Map<u32, Value>,
+        commands: &mut Vec<(RawCommand, Option<&'static str>)>,
+    ) {
+        for note in self.project_notes.iter().filter(|n| !n.is_deleted) {
+            let project_id = match projects.get(&note.project_id) {
+                Some(project_id) => project_id,
+                None => continue,
+            };
+            commands.push((
+                RawCommand::add(
+                    "project_note_add",
+                    json!({ "project_id": project_id, "content": note.content }),
+                    Uuid::new_v4(),
+                ),
+                // Like completed tasks, only those of the inbox are deleted
+                // on their own to undo
+                match project_id.is_u64() {
+                    true => Some("project_notes"),
+                    false => None,
+                },
+            ));
+        }
+    }
+
+    // The inbox cannot be added, so its tasks go to the inbox of the account,
+    // outside of sections
+    fn inbox_commands(
+        &self,
+        id: u32,
+        inbox: u32,
+        commands: &mut Vec<(RawCommand, Option<&'static str>)>,
+    ) {
+        let top = self
+            .archive
+            .items
+            .iter()
+            .filter(|i| i.project_id == id && i.parent_id.is_none());
+        for item in top {
+            for (i, mut command) in Inverse::readd(&self.archive, "tasks", item.id)
+                .into_iter()
+                .enumerate()
+            {
+                if command.command == "item_add" {
+                    command.args["project_id"] = json!(inbox);
+                    if let Some(args) = command.args.as_object_mut() {
+                        args.remove("section_id");
+                    }
+                }
+                commands.push((command, if i == 0 { Some("tasks") } else { None }));
+            }
+        }
+    }
+}
+
+impl Command for RestoreCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        // Parents and labels still in the account are used instead of
+        // restored
+        self.cache.refresh(&self.client)?;
+        let inbox = self
+            .cache
+            .projects
+            .iter()
+            .find(|p| p.inbox_project == Some(true))
+            .map(|p| p.id);
+
+        let mut commands: Vec<(RawCommand, Option<&str>)> = Vec::new();
+        // Where the projects of the archive are restored to, by id or temp id
+        let mut restored: Map<u32, Value> = Map::new();
+        let mut archived = Vec::new();
+        for id in self.selected()? {
+            let project = match self.archive.project(id) {
+                Some(project) => project,
+                None => continue,
+            };
+            if project.inbox_project == Some(true) {
+                match inbox {
+                    Some(inbox) => {
+                        self.inbox_commands(id, inbox, &mut commands);
+                        restored.insert(id, json!(inbox));
+                    }
+                    None => eprintln!("warning: no inbox to restore {} to, skipped", project.name),
+                }
+                continue;
+            }
+
+            let readd = Inverse::readd(&self.archive, "projects", id);
+            let added = readd
+                .iter()
+                .filter(|c| c.command == "project_add")
+                .filter_map(|c| c.temp_id);
+            for (project_id, temp_id) in self.subprojects(id).into_iter().zip(added) {
+                restored.insert(project_id, json!(temp_id));
+                if self
+                    .archive
+                    .project(project_id)
+                    .map_or(false, |p| p.is_archived)
+                {
+                    archived.push(temp_id);
+                }
+            }
+            for (i, mut command) in readd.into_iter().enumerate() {
+                if i == 0 {
+                    let parent = command.args.get("parent_id").and_then(|p| p.as_u64());
+                    if parent.map_or(false, |p| self.cache.project(p as u32).is_none()) {
+                        if let Some(args) = command.args.as_object_mut() {
+                            args.remove("parent_id");
+                        }
+                    }
+                }
+                commands.push((command, if i == 0 { Some("projects") } else { None }));
+            }
+        }
+
+        // Labels are matched by name, those missing are added first for tasks
+        // to refer to them. Without projects given, every missing label is.
+        let used: Vec<u64> = commands
+            .iter()
+            .filter_map(|(command, _)| command.args.get("labels"))
+            .filter_map(|labels| labels.as_array())
+            .flat_map(|labels| labels.iter().filter_map(|l| l.as_u64()))
+            .collect();
+        let mut labels: Map<u64, Value> = Map::new();
+        let mut label_commands = Vec::new();
+        for label in self.archive.labels.iter().filter(|l| !l.is_deleted) {
+            let id = label.id as u64;
+            if let Some(live) = self.cache.labels.iter().find(|l| l.name == label.name) {
+                labels.insert(id, json!(live.id));
+            } else if self.projects.is_empty() || used.contains(&id) {
+                let temp_id = Uuid::new_v4();
+                label_commands.push((
+                    RawCommand::add(
+                        "label_add",
+                        json!({
+                            "name": label.name,
+                            "color": label.color,
+                            "item_order": label.item_order,
+                            "is_favorite": label.is_favorite as i32,
+                        }),
+                        temp_id,
+                    ),
+                    Some("labels"),
+                ));
+                labels.insert(id, json!(temp_id));
+            }
+        }
+        for (command, _) in commands.iter_mut() {
+            if let Some(Value::Array(ids)) = command.args.get("labels") {
+                let ids: Vec<Value> = ids
+                    .iter()
+                    .filter_map(|l| l.as_u64().and_then(|l| labels.get(&l)).cloned())
+                    .collect();
+                command.args["labels"] = Value::Array(ids);
+            }
+        }
+        self.project_note_commands(&restored, &mut commands);
+        self.completed_commands(&restored, &mut commands);
+        // Projects are archived last, with everything in them restored
+        for temp_id in archived.into_iter() {
+            commands.push((
+                RawCommand::new("project_archive", json!({ "id": temp_id })),
+                None,
+            ));
+        }
+        label_commands.extend(commands);
+
+        let (commands, roots): (Vec<RawCommand>, Vec<Option<&str>>) =
+            label_commands.into_iter().unzip();
+        let subjects: Vec<String> = commands
+            .iter()
+            .map(|c| match c.args.get("content").or(c.args.get("name")) {
+                Some(Value::String(name)) => name.clone(),
+                _ => c.command.clone(),
+            })
+            .collect();
+
+        let results = self.client.sync_batch(&commands)?;
+        // Everything else is deleted with the projects and tasks it belongs to
+        let undo = results
+            .iter()
+            .zip(roots.iter())
+            .map(|(result, root)| match (result, root) {
+                (Ok(Some(id)), Some(resource_type)) => Inverse::delete(resource_type, *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("restore backup of {}", self.date);
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&subjects, results)
+    }
+}
@@ -0,0 +1,90 @@
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use dothis::api::client::TodoistClient;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::export;
+use super::super::query::Query;
+use super::http::{self, Request, READ_TIMEOUT};
+use super::Command;
+
+// Serves the iCalendar feed of export ical over HTTP, so calendar clients can
+// subscribe to it. Tasks are fetched again for every request.
+pub struct ServeIcalCommand {
+    client: TodoistClient,
+    cache: Cache,
+    address: String,
+    events: bool,
+    project: Option<u32>,
+    query: Option<Query>,
+}
+
+impl ServeIcalCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        address: &str,
+        events: bool,
+        project: Option<u32>,
+        query: Option<Query>,
+    ) -> ServeIcalCommand {
+        ServeIcalCommand {
+            client: client,
+            cache: cache,
+            address: address.to_string(),
+            events: events,
+            project: project,
+            query: query,
+        }
+    }
+
+    fn feed(&mut self) -> Result<String, DothisError> {
+        let response = self
+            .client
+            .get_resources(vec!["items", "projects", "labels"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let tasks = export::select(&self.cache, self.project, self.query.as_ref(), false);
+        Ok(export::ical(&tasks, &self.cache, self.events))
+    }
+
+    // Every request gets the feed, whatever its path. It is read before
+    // responding, as clients may wait for that, but not for long, as one idle
+    // connection would keep the others waiting.
+    fn respond(&mut self, stream: &mut TcpStream) -> Result<(), DothisError> {
+        stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT)))?;
+        Request::read(stream, 0)?;
+
+        let (status, content_type, body) = match self.feed() {
+            Ok(feed) => ("200 OK", "text/calendar; charset=utf-8", feed),
+            Err(err) => (
+                "502 Bad Gateway",
+                "text/plain; charset=utf-8",
+                format!("{}\n", err),
+            ),
+        };
+        http::respond(stream, status, content_type, &body)
+    }
+}
+
+impl Command for ServeIcalCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let listener = TcpListener::bind(&self.address)?;
+        eprintln!("serving tasks on http://{}/", self.address);
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    if let Err(err) = self.respond(&mut stream) {
+                        eprintln!("error: {}", err);
+                    }
+                }
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{DeleteCollaborator, ShareProject};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::{report_batch, Command};
+
+// Invites users to a project by email, or removes them from it. Invitations
+// cannot be taken back by undo, so nothing is recorded.
+pub struct ShareCommand {
+    client: TodoistClient,
+    cache: Cache,
+    project: u32,
+    emails: Vec<String>,
+    remove: bool,
+}
+
+impl ShareCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        project: u32,
+        emails: Vec<String>,
+        remove: bool,
+    ) -> ShareCommand {
+        ShareCommand {
+            client: client,
+            cache: cache,
+            project: project,
+            emails: emails,
+            remove: remove,
+        }
+    }
+}
+
+impl Command for ShareCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let results = match self.remove {
+            true => {
+                let commands: Vec<DeleteCollaborator> = self
+                    .emails
+                    .iter()
+                    .map(|email| DeleteCollaborator {
+                        project_id: self.project,
+                        email: email.clone(),
+                    })
+                    .collect();
+                self.client.sync_batch(&commands)?
+            }
+            false => {
+                let commands: Vec<ShareProject> = self
+                    .emails
+                    .iter()
+                    .map(|email| ShareProject {
+                        project_id: self.project,
+                        email: email.clone(),
+                    })
+                    .collect();
+                self.client.sync_batch(&commands)?
+            }
+        };
+
+        let response = self
+            .client
+            .get_resources(vec!["projects", "collaborators"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+        report_batch(&self.emails, results)
+    }
+}
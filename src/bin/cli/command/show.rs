@@ -0,0 +1,149 @@
+use prettytable::{format, Row, Table};
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::MemberState;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::{display_date, Command};
+
+pub struct ShowCommand {
+    client: TodoistClient,
+    cache: Cache,
+    resource_type: String,
+    id: u32,
+    date_format: Option<String>,
+    table: Table,
+}
+
+impl ShowCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        resource_type: &str,
+        id: u32,
+        date_format: Option<&str>,
+    ) -> ShowCommand {
+        ShowCommand {
+            client: client,
+            cache: cache,
+            resource_type: resource_type.to_string(),
+            id: id,
+            date_format: date_format.map(|f| f.to_string()),
+            table: Table::new(),
+        }
+    }
+
+    fn format_date(&self, date: &str) -> String {
+        display_date(date, self.date_format.as_deref(), self.cache.user.as_ref())
+    }
+
+    fn task_rows(&self) -> Result<Vec<Row>, DothisError> {
+        let task = match self.cache.item(self.id) {
+            Some(task) => task,
+            None => return Err(DothisError::EmptyResponseError),
+        };
+
+        let labels: Vec<&str> = task
+            .labels
+            .iter()
+            .filter_map(|id| self.cache.label(*id))
+            .map(|l| l.name.as_str())
+            .collect();
+        let due = task.due.as_ref().map_or(String::new(), |d| {
+            format!("{} ({})", d.string, self.format_date(&d.date))
+        });
+
+        let mut rows = vec![
+            row!["Content", task.content],
+            row!["Description", task.description],
+            row![
+                "Project",
+                self.cache.project(task.project_id).map_or("", |p| &p.name)
+            ],
+            row!["Due", due],
+            row!["Priority", task.priority],
+            row!["Labels", labels.join(", ")],
+            row!["Added", self.format_date(&task.date_added)],
+        ];
+        if let Some(uid) = task.responsible_uid {
+            rows.push(row!["Assignee", self.cache.user_name(uid as u32)]);
+        }
+        if let Some(completed) = &task.date_completed {
+            rows.push(row!["Completed", self.format_date(completed)]);
+        }
+
+        for note in self.cache.notes.iter().filter(|n| n.item_id == self.id) {
+            rows.push(row![self.format_date(&note.posted), note.content]);
+        }
+
+        Ok(rows)
+    }
+
+    fn project_rows(&self) -> Result<Vec<Row>, DothisError> {
+        let project = match self.cache.project(self.id) {
+            Some(project) => project,
+            None => return Err(DothisError::EmptyResponseError),
+        };
+        let tasks = self
+            .cache
+            .items
+            .iter()
+            .filter(|i| i.project_id == self.id && !i.checked)
+            .count();
+        let collaborators: Vec<String> = self
+            .cache
+            .collaborator_states
+            .iter()
+            .filter(|s| s.project_id == self.id && !s.is_deleted)
+            .map(|s| match s.state {
+                MemberState::Invited => format!("{} (invited)", self.cache.user_name(s.user_id)),
+                _ => self.cache.user_name(s.user_id),
+            })
+            .collect();
+
+        Ok(vec![
+            row!["Name", project.name],
+            row![
+                "Parent",
+                project
+                    .parent_id
+                    .and_then(|id| self.cache.project(id))
+                    .map_or("", |p| &p.name)
+            ],
+            row!["Color", project.color],
+            row!["Favorite", project.is_favorite],
+            row!["Shared", project.shared],
+            row!["Tasks", tasks],
+            row!["Collaborators", collaborators.join(", ")],
+        ])
+    }
+}
+
+impl Command for ShowCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self.client.get_resources(vec![
+            "items",
+            "projects",
+            "notes",
+            "labels",
+            "collaborators",
+            "user",
+        ])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let rows = match self.resource_type.as_str() {
+            "tasks" => self.task_rows()?,
+            "projects" => self.project_rows()?,
+            _ => return Err(DothisError::UnknownResource),
+        };
+        for row in rows.into_iter() {
+            self.table.add_row(row);
+        }
+
+        self.table.set_format(*format::consts::FORMAT_CLEAN);
+        self.table.printstd();
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::{CompletedCount, Streak};
+
+use super::super::error::DothisError;
+use super::Command;
+
+// Bars of the longest count, or of the goal if higher, are this wide
+const CHART_WIDTH: usize = 40;
+
+// Prints karma, goals and streaks, and charts of the tasks completed in the
+// last days and weeks
+pub struct StatsCommand {
+    client: TodoistClient,
+}
+
+impl StatsCommand {
+    pub fn new(client: TodoistClient) -> StatsCommand {
+        StatsCommand { client: client }
+    }
+}
+
+impl Command for StatsCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let stats = self.client.get_stats()?;
+        let goals = &stats.goals;
+
+        println!("Karma: {} ({})", stats.karma, stats.karma_trend);
+        println!("Completed: {} tasks", stats.completed_count);
+        println!(
+            "Daily goal: {}, streak {}",
+            goals.daily_goal,
+            streaks(&goals.current_daily_streak, &goals.max_daily_streak, "day")
+        );
+        println!(
+            "Weekly goal: {}, streak {}",
+            goals.weekly_goal,
+            streaks(
+                &goals.current_weekly_streak,
+                &goals.max_weekly_streak,
+                "week"
+            )
+        );
+
+        println!();
+        println!("Last {} days", stats.days_items.len());
+        chart(&stats.days_items, goals.daily_goal);
+        println!();
+        println!("Last {} weeks", stats.week_items.len());
+        chart(&stats.week_items, goals.weekly_goal);
+        Ok(())
+    }
+}
+
+fn streaks(current: &Streak, max: &Streak, unit: &str) -> String {
+    let plural = |count: u32| match count {
+        1 => format!("{} {}", count, unit),
+        _ => format!("{} {}s", count, unit),
+    };
+    format!("{} (best {})", plural(current.count), plural(max.count))
+}
+
+// Counts that reach the goal are marked with a *
+fn chart(counts: &[CompletedCount], goal: u32) {
+    let scale = counts
+        .iter()
+        .map(|c| c.total_completed)
+        .max()
+        .unwrap_or(0)
+        .max(goal)
+        .max(1) as usize;
+    let label_width = counts.iter().map(|c| c.date.len()).max().unwrap_or(0);
+
+    for count in counts.iter() {
+        let total = count.total_completed as usize;
+        let bar = "█".repeat(total * CHART_WIDTH / scale);
+        let mark = match goal > 0 && count.total_completed >= goal {
+            true => " *",
+            false => "",
+        };
+        println!(
+            "{:width$} {} {}{}",
+            count.date,
+            bar,
+            total,
+            mark,
+            width = label_width
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap as Map;
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::RawCommand;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::export::template::{self, Row};
+use super::super::journal::{Inverse, Journal};
+use super::{read_file, report_batch, Command};
+
+// Types of the rows added to projects
+const ROW_TYPES: [&str; 3] = ["section", "task", "note"];
+
+// Prints the sections, open tasks and notes of a project as a template
+pub struct ExportTemplateCommand {
+    client: TodoistClient,
+    cache: Cache,
+    project: u32,
+}
+
+impl ExportTemplateCommand {
+    pub fn new(client: TodoistClient, cache: Cache, project: u32) -> ExportTemplateCommand {
+        ExportTemplateCommand {
+            client: client,
+            cache: cache,
+            project: project,
+        }
+    }
+}
+
+impl Command for ExportTemplateCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self
+            .client
+            .get_resources(vec!["projects", "sections", "items", "notes"])?;
+        self.cache.update(response);
+        self.cache.save()?;
+
+        let output = template::render(self.project, &self.cache)?;
+        io::stdout().write_all(output.as_bytes())?;
+        Ok(())
+    }
+}
+
+// Adds the sections, tasks and notes of a template to a project, in one
+// batch where tasks refer to their section and parent by temp_id
+pub struct ApplyTemplateCommand {
+    client: TodoistClient,
+    journal: Journal,
+    file: String,
+    project: u32,
+    vars: Map<String, String>,
+}
+
+impl ApplyTemplateCommand {
+    pub fn new(
+        client: TodoistClient,
+        journal: Journal,
+        file: &str,
+        project: u32,
+        vars: Map<String, String>,
+    ) -> ApplyTemplateCommand {
+        ApplyTemplateCommand {
+            client: client,
+            journal: journal,
+            file: file.to_string(),
+            project: project,
+            vars: vars,
+        }
+    }
+
+    fn expand(&self, text: &str) -> Result<String, DothisError> {
+        template::expand(text, &self.vars).map_err(DothisError::UndefinedVariable)
+    }
+
+    // Due dates are sent as written, for Todoist to parse them
+    fn due(&self, row: &Row) -> Result<Value, DothisError> {
+        if row.date.is_empty() {
+            return Ok(Value::Null);
+        }
+        let mut due = json!({ "string": self.expand(&row.date)? });
+        if !row.date_lang.is_empty() {
+            due["lang"] = json!(row.date_lang);
+        }
+        if !row.timezone.is_empty() {
+            due["timezone"] = json!(row.timezone);
+        }
+        Ok(due)
+    }
+}
+
+impl Command for ApplyTemplateCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let rows = template::parse(&read_file(Some(&self.file))?)?;
+
+        let mut commands = Vec::new();
+        let mut subjects = Vec::new();
+        // Only sections and top level tasks outside of them are deleted to
+        // undo, the rest is deleted with them
+        let mut roots = Vec::new();
+        let mut section: Option<Uuid> = None;
+        // The last task added at every indent, parents of the next deeper one
+        let mut parents: Vec<Uuid> = Vec::new();
+
+        for row in rows.iter() {
+            // Rows of other types, like the meta rows of newer exports, have
+            // nothing to add
+            if !ROW_TYPES.contains(&row.kind.as_str()) {
+                eprintln!("warning: unknown template row type {}, skipped", row.kind);
+                continue;
+            }
+            let content = self.expand(&row.content)?;
+            let temp_id = Uuid::new_v4();
+            match row.kind.as_str() {
+                "section" => {
+                    commands.push(RawCommand::add(
+                        "section_add",
+                        json!({ "name": content, "project_id": self.project }),
+                        temp_id,
+                    ));
+                    roots.push(Some("sections"));
+                    section = Some(temp_id);
+                    parents.clear();
+                }
+                "task" => {
+                    parents.truncate(row.indent() - 1);
+                    let parent = parents.last().cloned();
+                    commands.push(RawCommand::add(
+                        "item_add",
+                        json!({
+                            "content": content,
+                            "project_id": self.project,
+                            "section_id": section,
+                            "parent_id": parent,
+                            "priority": row.priority(),
+                            "due": self.due(row)?,
+                        }),
+                        temp_id,
+                    ));
+                    roots.push(match (section, parent) {
+                        (None, None) => Some("tasks"),
+                        _ => None,
+                    });
+                    parents.push(temp_id);
+                }
+                "note" => {
+                    match parents.last() {
+                        Some(item) => {
+                            commands.push(RawCommand::add(
+                                "note_add",
+                                json!({ "item_id": item, "content": content }),
+                                temp_id,
+                            ));
+                            roots.push(None);
+                        }
+                        None => {
+                            commands.push(RawCommand::add(
+                                "project_note_add",
+                                json!({ "project_id": self.project, "content": content }),
+                                temp_id,
+                            ));
+                            roots.push(Some("project_notes"));
+                        }
+                    };
+                }
+                _ => continue,
+            }
+            subjects.push(content);
+        }
+
+        let results = self.client.sync_batch(&commands)?;
+        let undo = results
+            .iter()
+            .zip(roots.iter())
+            .map(|(result, root)| match (result, root) {
+                (Ok(Some(id)), Some(resource_type)) => Inverse::delete(resource_type, *id),
+                _ => Vec::new(),
+            })
+            .collect();
+        let description = format!("template apply {}", self.file);
+        self.journal.record_batch(&description, &results, undo)?;
+
+        report_batch(&subjects, results)
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap as Map;
+
+use dothis::api::client::{replace_temp_ids, TodoistClient};
+
+use super::super::error::DothisError;
+use super::super::journal::Journal;
+use super::Command;
+
+// Reverts the last write commands, most recent first. Entries are removed
+// from the journal once reverted. When some commands of an entry fail, only
+// those are kept, so undoing again retries them alone.
+pub struct UndoCommand {
+    client: TodoistClient,
+    journal: Journal,
+    steps: usize,
+}
+
+impl UndoCommand {
+    pub fn new(client: TodoistClient, journal: Journal, steps: usize) -> UndoCommand {
+        UndoCommand {
+            client: client,
+            journal: journal,
+            steps: steps,
+        }
+    }
+}
+
+impl Command for UndoCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        if self.journal.last().is_none() {
+            return Err(DothisError::EmptyHistory);
+        }
+
+        for _ in 0..self.steps {
+            let entry = match self.journal.last() {
+                Some(entry) => entry,
+                None => break,
+            };
+            // Commands refer to resources added by earlier ones through temp
+            // ids, which the client maps to ids when entries span requests
+            let results = self.client.sync_batch(&entry.undo)?;
+            if results.iter().any(|result| result.is_err()) {
+                let mut mapping = Map::new();
+                let mut failed = Vec::new();
+                let mut errors = Vec::new();
+                for (command, result) in entry.undo.iter().zip(results.into_iter()) {
+                    match (result, command.temp_id) {
+                        (Ok(Some(id)), Some(temp_id)) => {
+                            mapping.insert(temp_id.to_string(), id);
+                        }
+                        (Ok(_), _) => (),
+                        (Err(err), _) => {
+                            failed.push(command.clone());
+                            errors.push(err);
+                        }
+                    }
+                }
+                // Failed commands may refer to resources the others added
+                for command in failed.iter_mut() {
+                    command.args = replace_temp_ids(command.args.take(), &mapping);
+                }
+                self.journal.retain_last(failed);
+                self.journal.save()?;
+                return Err(DothisError::SyncError(errors.join(", ")));
+            }
+            println!("undone: {}", entry.description);
+
+            self.journal.pop();
+            self.journal.save()?;
+        }
+        Ok(())
+    }
+}
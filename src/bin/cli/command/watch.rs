@@ -0,0 +1,145 @@
+use std::collections::BTreeSet as Set;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use dothis::api::client::{TodoistClient, TodoistResponse};
+use dothis::api::resource::Item;
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::hook;
+use super::super::query::Query;
+use super::Command;
+
+const RESOURCES: [&str; 4] = ["items", "projects", "collaborators", "user"];
+
+// Polls for changes with incremental syncs, printing the tasks added,
+// updated, completed, uncompleted and deleted since the previous poll
+pub struct WatchCommand {
+    client: TodoistClient,
+    cache: Cache,
+    query: Option<Query>,
+    interval: Duration,
+    json: bool,
+    exec: Option<String>,
+    // Completed tasks leave the cache, those completed while watching are
+    // remembered to tell when they are uncompleted
+    completed: Set<u32>,
+}
+
+impl WatchCommand {
+    pub fn new(
+        client: TodoistClient,
+        cache: Cache,
+        query: Option<Query>,
+        interval: Duration,
+        json: bool,
+        exec: Option<&str>,
+    ) -> WatchCommand {
+        WatchCommand {
+            client: client,
+            cache: cache,
+            query: query,
+            interval: interval,
+            json: json,
+            exec: exec.map(|e| e.to_string()),
+            completed: Set::new(),
+        }
+    }
+
+    // Events are told from the cached state of the task, before the change
+    fn event(&mut self, item: &Item) -> Option<&'static str> {
+        let cached = self.cache.item(item.id);
+        let event = match (cached, item.is_deleted, item.checked) {
+            (None, true, _) => None,
+            (Some(_), true, _) => Some("deleted"),
+            (Some(_), false, true) => Some("completed"),
+            (None, false, true) => None,
+            (None, false, false) if self.completed.contains(&item.id) => Some("uncompleted"),
+            (None, false, false) => Some("added"),
+            (Some(cached), false, false) => {
+                match serde_json::to_value(cached).ok() == serde_json::to_value(item).ok() {
+                    true => None,
+                    false => Some("updated"),
+                }
+            }
+        };
+        match event {
+            Some("completed") => self.completed.insert(item.id),
+            Some("uncompleted") => self.completed.remove(&item.id),
+            _ => false,
+        };
+        event
+    }
+
+    fn report(&self, event: &str, item: &Item) {
+        let project = self.cache.name("projects", item.project_id);
+        match self.json {
+            true => println!(
+                "{}",
+                json!({ "event": event, "project": project, "item": item })
+            ),
+            false => println!(
+                "{}  {:11} {}  {}",
+                time::strftime("%H:%M:%S", &time::now()).unwrap_or_default(),
+                event,
+                project,
+                item.content
+            ),
+        }
+
+        // Hooks failing do not stop watching
+        if let Some(command) = &self.exec {
+            let payload = serde_json::to_value(item).unwrap_or(Value::Null);
+            if let Err(err) = hook::run(command, &format!("item:{}", event), &payload) {
+                eprintln!("warning: {}", err);
+            }
+        }
+    }
+
+    fn handle(&mut self, mut response: TodoistResponse) -> Result<(), DothisError> {
+        let items = response.items.take().unwrap_or_default();
+        let mut events = Vec::new();
+        for item in items.iter() {
+            if let Some(event) = self.event(item) {
+                events.push((event, item));
+            }
+        }
+
+        // Projects are merged first for events to show new ones by name
+        self.cache.merge(response);
+        for (event, item) in events.into_iter() {
+            if self.query.as_ref().map_or(true, |q| q.matches(item)) {
+                self.report(event, item);
+            }
+        }
+        self.cache.merge(TodoistResponse {
+            items: Some(items),
+            ..TodoistResponse::default()
+        });
+        self.cache.save()
+    }
+}
+
+impl Command for WatchCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let response = self.client.get_changes(RESOURCES.to_vec(), "*")?;
+        let mut sync_token = response.sync_token.clone();
+        self.cache.update(response);
+        self.cache.save()?;
+
+        loop {
+            thread::sleep(self.interval);
+            // Failed polls are retried on the next one, with the same token
+            match self.client.get_changes(RESOURCES.to_vec(), &sync_token) {
+                Ok(response) => {
+                    sync_token = response.sync_token.clone();
+                    self.handle(response)?;
+                }
+                Err(err) => eprintln!("warning: {}", err),
+            }
+        }
+    }
+}
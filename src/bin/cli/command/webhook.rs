@@ -0,0 +1,197 @@
+use std::collections::BTreeMap as Map;
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use dothis::api::client::TodoistResponse;
+use dothis::api::webhook::{self, EventData, WebhookEvent, SIGNATURE_HEADER};
+
+use super::super::cache::Cache;
+use super::super::error::DothisError;
+use super::super::hook;
+use super::http::{self, Request, READ_TIMEOUT};
+use super::{read_file, Command};
+
+// Todoist sends single resources, larger requests are refused unread
+const MAX_BODY: usize = 1024 * 1024;
+
+// The status and message answered to a request, with the event it delivered
+type Response = (&'static str, String, Option<WebhookEvent>);
+
+// Receives webhook requests of Todoist, verifying their signature. Their
+// resource updates the cache and is passed to the hooks of the profile
+// matching the event.
+pub struct WebhookServeCommand {
+    cache: Cache,
+    address: String,
+    secret: String,
+    hooks: Map<String, String>,
+}
+
+impl WebhookServeCommand {
+    pub fn new(
+        cache: Cache,
+        address: &str,
+        secret: &str,
+        hooks: Map<String, String>,
+    ) -> WebhookServeCommand {
+        WebhookServeCommand {
+            cache: cache,
+            address: address.to_string(),
+            secret: secret.to_string(),
+            hooks: hooks,
+        }
+    }
+
+    // Accepted events are returned to run their hooks
+    fn handle(&mut self, request: &Request) -> Result<Response, DothisError> {
+        if request.method() != "POST" {
+            return Ok((
+                "405 Method Not Allowed",
+                "only POST is accepted".to_string(),
+                None,
+            ));
+        }
+        let signature = request.header(SIGNATURE_HEADER).unwrap_or_default();
+        if !webhook::verify(&self.secret, &request.body, signature) {
+            return Ok(("401 Unauthorized", "invalid signature".to_string(), None));
+        }
+        let event: WebhookEvent = match serde_json::from_slice(&request.body) {
+            Ok(event) => event,
+            Err(err) => return Ok(("400 Bad Request", err.to_string(), None)),
+        };
+
+        let (subject, changes) = match event.data() {
+            Ok(EventData::Item(item)) => (
+                item.content.clone(),
+                TodoistResponse {
+                    items: Some(vec![item]),
+                    ..TodoistResponse::default()
+                },
+            ),
+            Ok(EventData::Note(note)) => (
+                note.content.clone(),
+                TodoistResponse {
+                    notes: Some(vec![note]),
+                    ..TodoistResponse::default()
+                },
+            ),
+            Ok(EventData::Project(project)) => (
+                project.name.clone(),
+                TodoistResponse {
+                    projects: Some(vec![project]),
+                    ..TodoistResponse::default()
+                },
+            ),
+            Ok(EventData::Other(_)) => (String::new(), TodoistResponse::default()),
+            Err(err) => return Ok(("400 Bad Request", err.to_string(), None)),
+        };
+        println!("{} {}", event.event_name, subject);
+        self.cache.merge(changes);
+        self.cache.save()?;
+        Ok(("200 OK", "ok".to_string(), Some(event)))
+    }
+
+    // Hooks failing are reported, Todoist would only retry the request
+    fn run_hooks(&self, event: &WebhookEvent) {
+        for (pattern, command) in self.hooks.iter() {
+            if matches(pattern, &event.event_name) {
+                if let Err(err) = hook::run(command, &event.event_name, &event.event_data) {
+                    eprintln!("warning: {}", err);
+                }
+            }
+        }
+    }
+
+    // Requests are handled one at a time, so an idle connection is only
+    // waited for briefly. Hooks run once Todoist has its answer, as it sends
+    // the event again when waiting too long.
+    fn respond(&mut self, stream: &mut TcpStream) -> Result<(), DothisError> {
+        stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT)))?;
+        let (status, message, event) = match Request::read(stream, MAX_BODY)? {
+            Some(request) => self.handle(&request)?,
+            None => (
+                "413 Payload Too Large",
+                "request too large".to_string(),
+                None,
+            ),
+        };
+        http::respond(
+            stream,
+            status,
+            "text/plain; charset=utf-8",
+            &format!("{}\n", message),
+        )?;
+        if let Some(event) = event {
+            self.run_hooks(&event);
+        }
+        Ok(())
+    }
+}
+
+impl Command for WebhookServeCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let listener = TcpListener::bind(&self.address)?;
+        eprintln!("receiving webhooks on http://{}/", self.address);
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    if let Err(err) = self.respond(&mut stream) {
+                        eprintln!("error: {}", err);
+                    }
+                }
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+        Ok(())
+    }
+}
+
+// Hooks are configured for an event, like item:completed, every event of a
+// resource, like item:*, or every event, *
+fn matches(pattern: &str, event_name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event_name.starts_with(prefix),
+        None => pattern == event_name,
+    }
+}
+
+// Prints the signature of a payload, to send signed sample requests to a
+// local server
+pub struct WebhookSignCommand {
+    secret: String,
+    file: Option<String>,
+}
+
+impl WebhookSignCommand {
+    pub fn new(secret: &str, file: Option<&str>) -> WebhookSignCommand {
+        WebhookSignCommand {
+            secret: secret.to_string(),
+            file: file.map(|f| f.to_string()),
+        }
+    }
+}
+
+impl Command for WebhookSignCommand {
+    fn execute(&mut self) -> Result<(), DothisError> {
+        let payload = read_file(self.file.as_deref())?;
+        println!("{}", webhook::sign(&self.secret, payload.as_bytes()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_events() {
+        assert!(matches("item:completed", "item:completed"));
+        assert!(!matches("item:completed", "item:added"));
+        assert!(matches("item:*", "item:added"));
+        assert!(matches("item:*", "item:completed"));
+        assert!(!matches("item:*", "note:added"));
+        assert!(!matches("item:*", "items:added"));
+        assert!(matches("*", "project:archived"));
+    }
+}
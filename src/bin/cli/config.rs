@@ -0,0 +1,208 @@
+use std::collections::BTreeMap as Map;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+use std::process;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::DothisError;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+// Keys accepted by `dothis config get/set`, all of them but default_profile
+// are stored per profile
+pub const CONFIG_KEYS: [&str; 10] = [
+    "default_profile",
+    "token",
+    "token_command",
+    "api_url",
+    "default_project",
+    "default_view",
+    "date_format",
+    "color_theme",
+    "webhook_secret",
+    "todo_project",
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: Map<String, Profile>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    // Command whose stdout is the API token, for example `pass show todoist`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_project: Option<String>,
+    // Resource listed when running `dothis list` without a resource
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_view: Option<String>,
+    // strftime-like format used to display dates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_theme: Option<String>,
+    // Client secret of the app webhooks are registered for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    // Project the tasks of git todo-scan go to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub todo_project: Option<String>,
+    // Shell commands run for webhook events, keyed by event like
+    // item:completed, item:* or *. Tables go last in TOML.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub hooks: Map<String, String>,
+}
+
+impl Config {
+    pub fn path() -> Result<PathBuf, DothisError> {
+        match dirs::config_dir() {
+            Some(dir) => Ok(dir.join("dothis").join("config.toml")),
+            None => Err(DothisError::MissingConfigDir),
+        }
+    }
+
+    // A missing config file is not an error: it is treated as an empty config
+    pub fn load() -> Result<Config, DothisError> {
+        let path = Config::path()?;
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    pub fn save(&self) -> Result<(), DothisError> {
+        let path = Config::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string(self)?;
+        // Profiles can hold a token, so the file is only readable by the owner
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        // mode only applies to newly created files
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+
+    // Name of the profile to use: the explicitly requested one, the configured
+    // default_profile or DEFAULT_PROFILE, in that order
+    pub fn profile_name(&self, requested: Option<&str>) -> String {
+        requested
+            .or(self.default_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string()
+    }
+
+    // Only an explicitly requested profile has to exist in the config file
+    pub fn profile(&self, requested: Option<&str>) -> Result<Profile, DothisError> {
+        let name = self.profile_name(requested);
+        match self.profiles.get(&name) {
+            Some(profile) => Ok(profile.clone()),
+            None if requested.is_none() => Ok(Profile::default()),
+            None => Err(DothisError::UnknownProfile(name)),
+        }
+    }
+
+    pub fn get(&self, profile: &str, key: &str) -> Result<Option<String>, DothisError> {
+        match key {
+            "default_profile" => Ok(self.default_profile.clone()),
+            other => match self.profiles.get(profile) {
+                Some(p) => p.get(other),
+                None => Profile::default().get(other),
+            },
+        }
+    }
+
+    pub fn set(&mut self, profile: &str, key: &str, value: &str) -> Result<(), DothisError> {
+        match key {
+            "default_profile" => {
+                self.default_profile = Some(value.to_string());
+                Ok(())
+            }
+            other => self
+                .profiles
+                .entry(profile.to_string())
+                .or_insert_with(Profile::default)
+                .set(other, value),
+        }
+    }
+}
+
+impl Profile {
+    fn field(&mut self, key: &str) -> Result<&mut Option<String>, DothisError> {
+        match key {
+            "token" => Ok(&mut self.token),
+            "token_command" => Ok(&mut self.token_command),
+            "api_url" => Ok(&mut self.api_url),
+            "default_project" => Ok(&mut self.default_project),
+            "default_view" => Ok(&mut self.default_view),
+            "date_format" => Ok(&mut self.date_format),
+            "color_theme" => Ok(&mut self.color_theme),
+            "webhook_secret" => Ok(&mut self.webhook_secret),
+            "todo_project" => Ok(&mut self.todo_project),
+            other => Err(DothisError::UnknownConfigKey(other.to_string())),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, DothisError> {
+        Ok(self.clone().field(key)?.take())
+    }
+
+    // An empty value unsets the key
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), DothisError> {
+        let field = self.field(key)?;
+        *field = match value {
+            "" => None,
+            v => Some(v.to_string()),
+        };
+        Ok(())
+    }
+
+    // Resolves the API token from the profile, running token_command if no
+    // token is stored in plain text
+    pub fn token(&self) -> Result<Option<String>, DothisError> {
+        if let Some(token) = &self.token {
+            return Ok(Some(token.clone()));
+        }
+        match &self.token_command {
+            Some(command) => run_token_command(command).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+fn run_token_command(command: &str) -> Result<String, DothisError> {
+    let output = process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stderr(process::Stdio::inherit())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(DothisError::TokenCommandError(command.to_string()));
+    }
+    // Credential helpers like pass may print more than one line,
+    // the token is always the first one
+    match String::from_utf8_lossy(&output.stdout).lines().next() {
+        Some(line) if !line.trim().is_empty() => Ok(line.trim().to_string()),
+        _ => Err(DothisError::TokenCommandError(command.to_string())),
+    }
+}
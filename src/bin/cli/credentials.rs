@@ -0,0 +1,80 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+
+use super::error::DothisError;
+
+// Tokens stored by `dothis login` live outside of the config file, one file per
+// profile, readable only by the owner
+fn token_path(profile: &str) -> Result<PathBuf, DothisError> {
+    match dirs::data_dir() {
+        Some(dir) => Ok(dir.join("dothis").join("tokens").join(profile)),
+        None => Err(DothisError::MissingConfigDir),
+    }
+}
+
+pub fn load_token(profile: &str) -> Result<Option<String>, DothisError> {
+    let path = token_path(profile)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let token = fs::read_to_string(&path)?;
+    match token.trim() {
+        "" => Ok(None),
+        t => Ok(Some(t.to_string())),
+    }
+}
+
+pub fn store_token(profile: &str, token: &str) -> Result<PathBuf, DothisError> {
+    let path = token_path(profile)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        fs::set_permissions(parent, fs::Permissions::from_mode(0o700))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)?;
+    // mode only applies to newly created files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    writeln!(file, "{}", token)?;
+
+    Ok(path)
+}
+
+// Reads a line from stdin without echoing it when stdin is a terminal,
+// so tokens can also be piped in from a password manager
+pub fn read_secret(prompt: &str) -> Result<String, DothisError> {
+    let stdin = io::stdin();
+    let is_tty = unsafe { libc::isatty(libc::STDIN_FILENO) } == 1;
+    let mut line = String::new();
+
+    if !is_tty {
+        stdin.lock().read_line(&mut line)?;
+        return Ok(line.trim().to_string());
+    }
+
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+
+    let mut termios: libc::termios = unsafe { mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+        return Err(DothisError::IoError(io::Error::last_os_error()));
+    }
+    let original = termios;
+    termios.c_lflag &= !libc::ECHO;
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+
+    let read = stdin.lock().read_line(&mut line);
+
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &original) };
+    eprintln!();
+    read?;
+
+    Ok(line.trim().to_string())
+}
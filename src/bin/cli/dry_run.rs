@@ -0,0 +1,179 @@
+use serde_json::{Map, Value};
+
+use dothis::api::client::{TodoistCommand, TodoistQuery};
+
+use super::cache::Cache;
+use super::error::DothisError;
+
+// Prints the query a write command would have sent, followed by what it
+// would change, compared to the cached resources. Commands stop at their first
+// request, so the ones that would follow with the ids of added resources, like
+// completions of imported tasks, are not known.
+pub fn report(url: &str, query: &TodoistQuery, cache: &Cache) -> Result<(), DothisError> {
+    println!("Would send to {}:", url);
+    println!("{}", serde_json::to_string_pretty(&query.redacted())?);
+
+    println!();
+    println!("Changes:");
+    for command in query.commands().iter() {
+        for line in describe(command, cache) {
+            println!("  {}", line);
+        }
+    }
+
+    if query
+        .commands()
+        .iter()
+        .any(|c| c.command().ends_with("_add"))
+    {
+        println!();
+        println!(
+            "Any later request needing the ids of added resources, like completions, \
+             dependencies or items of templates, is not shown."
+        );
+    }
+    Ok(())
+}
+
+// Commands are named after the resource and the action, like item_update or
+// project_note_add
+fn describe(command: &TodoistCommand, cache: &Cache) -> Vec<String> {
+    let name = command.command();
+    let prefix = ["project_note", "live_notifications"]
+        .iter()
+        .find(|kind| name.starts_with(&format!("{}_", kind)));
+    let (kind, action) = match prefix {
+        Some(kind) => (*kind, &name[kind.len() + 1..]),
+        None => {
+            let mut parts = name.splitn(2, '_');
+            (
+                parts.next().unwrap_or_default(),
+                parts.next().unwrap_or_default(),
+            )
+        }
+    };
+    let empty = Map::new();
+    let args = command.args().as_object().unwrap_or(&empty);
+    let id = args
+        .get("id")
+        .and_then(|id| id.as_u64())
+        .map(|id| id as u32);
+    let cached = id.and_then(|id| snapshot(cache, kind, id));
+    let name = match (&cached, id) {
+        (Some(cached), _) => quoted_name(cached),
+        (None, Some(id)) => id.to_string(),
+        (None, None) => String::new(),
+    };
+
+    match action {
+        "add" => {
+            let mut lines = vec![format!(
+                "+ {} {} (temp id {})",
+                display_kind(kind),
+                quoted_name(command.args()),
+                command.temp_id()
+            )];
+            for (field, value) in args.iter() {
+                if field != "content" && field != "name" {
+                    lines.push(format!(
+                        "    {}: {}",
+                        field,
+                        show(cache, kind, field, value)
+                    ));
+                }
+            }
+            lines
+        }
+        "delete" => vec![format!("- {} {}", display_kind(kind), name)],
+        "update" | "move" => {
+            let mut lines = vec![format!("~ {} {}", display_kind(kind), name)];
+            for (field, value) in args.iter().filter(|(field, _)| *field != "id") {
+                let old = cached
+                    .as_ref()
+                    .and_then(|c| c.get(field))
+                    .map_or("unknown".to_string(), |old| show(cache, kind, field, old));
+                let new = show(cache, kind, field, value);
+                if old != new {
+                    lines.push(format!("    {}: {} -> {}", field, old, new));
+                }
+            }
+            lines
+        }
+        other => vec![format!(
+            "* {} {} {}",
+            other.replace('_', " "),
+            display_kind(kind),
+            name
+        )],
+    }
+}
+
+fn display_kind(kind: &str) -> &str {
+    match kind {
+        "item" => "task",
+        "project_note" => "project note",
+        "live_notifications" => "notification",
+        other => other,
+    }
+}
+
+fn snapshot(cache: &Cache, kind: &str, id: u32) -> Option<Value> {
+    let value = match kind {
+        "item" => cache.item(id).map(serde_json::to_value),
+        "project" => cache.project(id).map(serde_json::to_value),
+        "label" => cache.label(id).map(serde_json::to_value),
+        "note" => cache.note(id).map(serde_json::to_value),
+        "section" => cache.section(id).map(serde_json::to_value),
+        _ => None,
+    };
+    value.and_then(|v| v.ok())
+}
+
+fn quoted_name(resource: &Value) -> String {
+    match resource.get("content").or(resource.get("name")) {
+        Some(Value::String(name)) => format!("\"{}\"", name),
+        _ => String::new(),
+    }
+}
+
+// Ids are shown with the name of what they refer to, and due dates by the
+// string they were set with
+fn show(cache: &Cache, kind: &str, field: &str, value: &Value) -> String {
+    let referred = match field {
+        "project_id" => Some("project"),
+        "section_id" => Some("section"),
+        "item_id" => Some("item"),
+        "parent_id" if kind == "item" => Some("item"),
+        "parent_id" => Some("project"),
+        _ => None,
+    };
+    match (referred, value) {
+        (_, Value::Null) => "none".to_string(),
+        (Some(referred), Value::Number(id)) => {
+            let id = id.as_u64().unwrap_or_default() as u32;
+            match snapshot(cache, referred, id) {
+                Some(resource) => format!("{} ({})", quoted_name(&resource), id),
+                None => id.to_string(),
+            }
+        }
+        (_, Value::Array(values)) if field == "labels" => {
+            let names: Vec<String> = values
+                .iter()
+                .map(|v| match v.as_u64().and_then(|id| cache.label(id as u32)) {
+                    Some(label) => format!("@{}", label.name),
+                    None => v.to_string(),
+                })
+                .collect();
+            format!("[{}]", names.join(", "))
+        }
+        (_, Value::Number(uid)) if field == "responsible_uid" => {
+            let uid = uid.as_u64().unwrap_or_default() as u32;
+            format!("{} ({})", cache.user_name(uid), uid)
+        }
+        (_, Value::Object(due)) if field == "due" => match due.get("string").or(due.get("date")) {
+            Some(string) => string.to_string(),
+            None => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
@@ -1,7 +1,9 @@
 use std::error;
 use std::fmt;
+use std::io;
 
 use dothis::api::client::TodoistApiError;
+use dothis::api::resolve::ResolveError;
 
 #[derive(Debug)]
 pub enum DothisError {
@@ -10,6 +12,40 @@ pub enum DothisError {
     UnknownResource,
     MissingCommand,
     UnknownCommand,
+    IoError(io::Error),
+    ConfigReadError(toml::de::Error),
+    ConfigWriteError(toml::ser::Error),
+    MissingConfigDir,
+    MissingToken,
+    TokenCommandError(String),
+    UnknownProfile(String),
+    UnknownConfigKey(String),
+    JsonError(serde_json::Error),
+    SyncError(String),
+    ResolveError(ResolveError),
+    QueryError(String),
+    BatchError(usize, usize),
+    EmptyHistory,
+    CsvError(csv::Error),
+    UndefinedVariable(String),
+    UnsupportedArchive(u32),
+    NotCollaborator(String, String),
+    NotInvitation(u32),
+    HookError(String),
+    MissingWebhookSecret,
+    DaemonRunning(String),
+    NonLocalAddress(String),
+    GitError(String),
+    HookExists(String),
+    MissingTodoProject,
+    MissingArgument(String),
+    NothingPicked,
+    Unconfirmed(String, String),
+    EditorError(String),
+    InvalidDocument(String, String),
+    EmptyContent,
+    EditorConflict,
+    DescriptionConflict,
 }
 
 impl error::Error for DothisError {
@@ -20,6 +56,40 @@ impl error::Error for DothisError {
             DothisError::UnknownResource => None,
             DothisError::MissingCommand => None,
             DothisError::UnknownCommand => None,
+            DothisError::IoError(ref err) => Some(err),
+            DothisError::ConfigReadError(ref err) => Some(err),
+            DothisError::ConfigWriteError(ref err) => Some(err),
+            DothisError::MissingConfigDir => None,
+            DothisError::MissingToken => None,
+            DothisError::TokenCommandError(_) => None,
+            DothisError::UnknownProfile(_) => None,
+            DothisError::UnknownConfigKey(_) => None,
+            DothisError::JsonError(ref err) => Some(err),
+            DothisError::SyncError(_) => None,
+            DothisError::ResolveError(ref err) => Some(err),
+            DothisError::QueryError(_) => None,
+            DothisError::BatchError(_, _) => None,
+            DothisError::EmptyHistory => None,
+            DothisError::CsvError(ref err) => Some(err),
+            DothisError::UndefinedVariable(_) => None,
+            DothisError::UnsupportedArchive(_) => None,
+            DothisError::NotCollaborator(_, _) => None,
+            DothisError::NotInvitation(_) => None,
+            DothisError::HookError(_) => None,
+            DothisError::MissingWebhookSecret => None,
+            DothisError::DaemonRunning(_) => None,
+            DothisError::NonLocalAddress(_) => None,
+            DothisError::GitError(_) => None,
+            DothisError::HookExists(_) => None,
+            DothisError::MissingTodoProject => None,
+            DothisError::MissingArgument(_) => None,
+            DothisError::NothingPicked => None,
+            DothisError::Unconfirmed(_, _) => None,
+            DothisError::EditorError(_) => None,
+            DothisError::InvalidDocument(_, _) => None,
+            DothisError::EmptyContent => None,
+            DothisError::EditorConflict => None,
+            DothisError::DescriptionConflict => None,
         }
     }
 }
@@ -35,6 +105,91 @@ impl fmt::Display for DothisError {
             DothisError::UnknownCommand => write!(f, "unknown command"),
             DothisError::EmptyResponseError => write!(f, "no resources found"),
             DothisError::UnknownResource => write!(f, "unknown resource"),
+            DothisError::IoError(ref err) => err.fmt(f),
+            DothisError::ConfigReadError(ref err) => write!(f, "invalid config file: {}", err),
+            DothisError::ConfigWriteError(ref err) => {
+                write!(f, "could not write config file: {}", err)
+            }
+            DothisError::MissingConfigDir => write!(f, "could not find a config directory"),
+            DothisError::MissingToken => write!(
+                f,
+                "missing API token, use login, a profile token or token_command, or TODOIST_API_TOKEN"
+            ),
+            DothisError::TokenCommandError(ref command) => {
+                write!(f, "token command failed: {}", command)
+            }
+            DothisError::UnknownProfile(ref name) => write!(f, "unknown profile: {}", name),
+            DothisError::UnknownConfigKey(ref key) => write!(f, "unknown config key: {}", key),
+            DothisError::JsonError(ref err) => err.fmt(f),
+            DothisError::SyncError(ref errors) => write!(f, "sync failed: {}", errors),
+            DothisError::ResolveError(ref err) => err.fmt(f),
+            DothisError::QueryError(ref term) => write!(f, "unsupported query term: {}", term),
+            DothisError::BatchError(failed, total) => {
+                write!(f, "{} of {} commands failed", failed, total)
+            }
+            DothisError::EmptyHistory => write!(f, "nothing to undo"),
+            DothisError::CsvError(ref err) => write!(f, "invalid CSV: {}", err),
+            DothisError::UndefinedVariable(ref name) => {
+                write!(f, "undefined template variable: {}, set it with --var", name)
+            }
+            DothisError::UnsupportedArchive(version) => write!(
+                f,
+                "archive version {} is newer than supported, update dothis to restore it",
+                version
+            ),
+            DothisError::NotCollaborator(ref user, ref project) => {
+                write!(f, "{} is not a collaborator of project {}", user, project)
+            }
+            DothisError::NotInvitation(id) => {
+                write!(f, "notification {} is not a pending share invitation", id)
+            }
+            DothisError::HookError(ref command) => write!(f, "hook failed: {}", command),
+            DothisError::MissingWebhookSecret => write!(
+                f,
+                "missing webhook secret, use --secret, DOTHIS_WEBHOOK_SECRET or a profile webhook_secret"
+            ),
+            DothisError::DaemonRunning(ref path) => {
+                write!(f, "a daemon is already listening on {}", path)
+            }
+            DothisError::NonLocalAddress(ref address) => write!(
+                f,
+                "{} is not a loopback address, the daemon only listens on local ones",
+                address
+            ),
+            DothisError::GitError(ref message) => write!(f, "git failed: {}", message),
+            DothisError::HookExists(ref path) => write!(
+                f,
+                "a post-commit hook already exists at {}, use --force to replace it",
+                path
+            ),
+            DothisError::MissingTodoProject => write!(
+                f,
+                "missing project for TODO tasks, use --project or a profile todo_project"
+            ),
+            DothisError::MissingArgument(ref name) => write!(
+                f,
+                "missing {}, it can only be picked when running in a terminal",
+                name
+            ),
+            DothisError::NothingPicked => write!(f, "nothing picked"),
+            DothisError::Unconfirmed(ref query, ref resource) => write!(
+                f,
+                "\"{}\" only approximately matches {}, use its id, its full name or --yes",
+                query, resource
+            ),
+            DothisError::EditorError(ref editor) => {
+                write!(f, "editor {} exited with an error", editor)
+            }
+            DothisError::InvalidDocument(ref path, ref reason) => {
+                write!(f, "{}, the document was kept in {}", reason, path)
+            }
+            DothisError::EmptyContent => write!(f, "the task has no content, nothing was added"),
+            DothisError::EditorConflict => {
+                write!(f, "changes cannot be given along with --editor")
+            }
+            DothisError::DescriptionConflict => {
+                write!(f, "--description cannot be given along with --description-file")
+            }
         }
     }
 }
@@ -45,6 +200,42 @@ impl From<TodoistApiError> for DothisError {
     }
 }
 
+impl From<io::Error> for DothisError {
+    fn from(err: io::Error) -> DothisError {
+        DothisError::IoError(err)
+    }
+}
+
+impl From<toml::de::Error> for DothisError {
+    fn from(err: toml::de::Error) -> DothisError {
+        DothisError::ConfigReadError(err)
+    }
+}
+
+impl From<toml::ser::Error> for DothisError {
+    fn from(err: toml::ser::Error) -> DothisError {
+        DothisError::ConfigWriteError(err)
+    }
+}
+
+impl From<serde_json::Error> for DothisError {
+    fn from(err: serde_json::Error) -> DothisError {
+        DothisError::JsonError(err)
+    }
+}
+
+impl From<ResolveError> for DothisError {
+    fn from(err: ResolveError) -> DothisError {
+        DothisError::ResolveError(err)
+    }
+}
+
+impl From<csv::Error> for DothisError {
+    fn from(err: csv::Error) -> DothisError {
+        DothisError::CsvError(err)
+    }
+}
+
 impl From<DothisError> for i32 {
     fn from(err: DothisError) -> Self {
         match err {
@@ -53,6 +244,40 @@ impl From<DothisError> for i32 {
             DothisError::UnknownResource => 64,
             DothisError::MissingCommand => 64,
             DothisError::UnknownCommand => 64,
+            DothisError::IoError(_) => 74,
+            DothisError::ConfigReadError(_) => 78,
+            DothisError::ConfigWriteError(_) => 74,
+            DothisError::MissingConfigDir => 78,
+            DothisError::MissingToken => 78,
+            DothisError::TokenCommandError(_) => 78,
+            DothisError::UnknownProfile(_) => 78,
+            DothisError::UnknownConfigKey(_) => 64,
+            DothisError::JsonError(_) => 65,
+            DothisError::SyncError(_) => 69,
+            DothisError::ResolveError(_) => 65,
+            DothisError::QueryError(_) => 64,
+            DothisError::BatchError(_, _) => 69,
+            DothisError::EmptyHistory => 65,
+            DothisError::CsvError(_) => 65,
+            DothisError::UndefinedVariable(_) => 64,
+            DothisError::UnsupportedArchive(_) => 65,
+            DothisError::NotCollaborator(_, _) => 65,
+            DothisError::NotInvitation(_) => 65,
+            DothisError::HookError(_) => 69,
+            DothisError::MissingWebhookSecret => 78,
+            DothisError::DaemonRunning(_) => 69,
+            DothisError::NonLocalAddress(_) => 64,
+            DothisError::GitError(_) => 69,
+            DothisError::HookExists(_) => 64,
+            DothisError::MissingTodoProject => 78,
+            DothisError::MissingArgument(_) => 64,
+            DothisError::NothingPicked => 64,
+            DothisError::Unconfirmed(_, _) => 64,
+            DothisError::EditorError(_) => 69,
+            DothisError::InvalidDocument(_, _) => 65,
+            DothisError::EmptyContent => 65,
+            DothisError::EditorConflict => 64,
+            DothisError::DescriptionConflict => 64,
         }
     }
 }
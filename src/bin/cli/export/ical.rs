@@ -0,0 +1,233 @@
+use dothis::api::resource::{DueDate, Item};
+
+use super::super::cache::Cache;
+
+// Lines longer than this many octets are folded, as RFC 5545 requires
+const MAX_LINE: usize = 75;
+
+// Renders tasks with a due date as an iCalendar feed, either as to-dos or as
+// events for calendar clients that do not show to-dos
+pub fn render(tasks: &[&Item], cache: &Cache, events: bool) -> String {
+    let stamp = time::strftime("%Y%m%dT%H%M%SZ", &time::now_utc()).unwrap_or_default();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//dothis//dothis//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Todoist".to_string(),
+    ];
+
+    for task in tasks.iter() {
+        let due = match &task.due {
+            Some(due) => due,
+            None => continue,
+        };
+        let component = match events {
+            true => "VEVENT",
+            false => "VTODO",
+        };
+
+        lines.push(format!("BEGIN:{}", component));
+        // Ids never change, so clients update entries instead of duplicating them
+        lines.push(format!("UID:{}@dothis.todoist.com", task.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape(&task.content)));
+        match events {
+            true => lines.push(format!("DTSTART{}", date_value(due))),
+            false => lines.push(format!("DUE{}", date_value(due))),
+        }
+        if due.is_recurring {
+            if let Some(rule) = rrule(&due.string) {
+                // Recurrences start from DTSTART, which to-dos only have for them
+                if !events {
+                    lines.push(format!("DTSTART{}", date_value(due)));
+                }
+                lines.push(format!("RRULE:{}", rule));
+            }
+        }
+        if let Some(priority) = priority(task.priority) {
+            lines.push(format!("PRIORITY:{}", priority));
+        }
+
+        let mut categories: Vec<String> = task
+            .labels
+            .iter()
+            .filter_map(|id| cache.label(*id))
+            .map(|l| escape(&l.name))
+            .collect();
+        if let Some(project) = cache.project(task.project_id) {
+            categories.insert(0, escape(&project.name));
+        }
+        if !categories.is_empty() {
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        if !events {
+            lines.push(
+                match task.checked {
+                    true => "STATUS:COMPLETED",
+                    false => "STATUS:NEEDS-ACTION",
+                }
+                .to_string(),
+            );
+        }
+        lines.push(format!("END:{}", component));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+// Todoist dates are full days, floating times or UTC times, like
+// 2020-01-31, 2020-01-31T09:00:00 or 2020-01-31T09:00:00Z
+fn date_value(due: &DueDate) -> String {
+    let value: String = due
+        .date
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect();
+    match due.date.contains('T') {
+        true => format!(":{}", value),
+        false => format!(";VALUE=DATE:{}", value),
+    }
+}
+
+// Todoist priorities go from 1 (normal) to 4 (urgent), iCalendar ones from 1
+// (highest) to 9 (lowest), with 0 meaning undefined
+fn priority(priority: i32) -> Option<i32> {
+    match priority {
+        4 => Some(1),
+        3 => Some(5),
+        2 => Some(9),
+        _ => None,
+    }
+}
+
+// Recurrence rule for the common forms of recurring due strings, like
+// "every day", "every 2 weeks", "every other month" or "every mon, fri".
+// Anything else, like "every last day" or "every 3rd friday", has no rule.
+pub fn rrule(string: &str) -> Option<String> {
+    let string = string.to_lowercase();
+    // Times are part of the due date, not of the rule
+    let string = match string.find(" at ") {
+        Some(index) => &string[..index],
+        None => string.as_str(),
+    };
+    let words: Vec<&str> = string
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty() && *w != "and")
+        .collect();
+
+    match words.as_slice() {
+        ["daily"] => return Some("FREQ=DAILY".to_string()),
+        ["weekly"] => return Some("FREQ=WEEKLY".to_string()),
+        ["monthly"] => return Some("FREQ=MONTHLY".to_string()),
+        ["yearly"] | ["annually"] => return Some("FREQ=YEARLY".to_string()),
+        ["every", "weekday"] => return Some("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR".to_string()),
+        ["every", "weekend"] => return Some("FREQ=WEEKLY;BYDAY=SA,SU".to_string()),
+        _ => (),
+    }
+
+    // Like "every mon, fri", weekdays without an interval
+    if words.len() > 1 && words[0] == "every" {
+        let days: Option<Vec<&str>> = words[1..].iter().map(|d| weekday(d)).collect();
+        if let Some(days) = days {
+            return Some(format!("FREQ=WEEKLY;BYDAY={}", days.join(",")));
+        }
+    }
+
+    let (interval, unit) = match words.as_slice() {
+        ["every", unit] => (1, *unit),
+        ["every", "other", unit] => (2, *unit),
+        ["every", count, unit] => (count.parse::<u32>().ok()?, *unit),
+        _ => return None,
+    };
+    let rule = match unit.trim_end_matches('s') {
+        "day" => "FREQ=DAILY".to_string(),
+        "week" => "FREQ=WEEKLY".to_string(),
+        "month" => "FREQ=MONTHLY".to_string(),
+        "year" => "FREQ=YEARLY".to_string(),
+        day => format!("FREQ=WEEKLY;BYDAY={}", weekday(day)?),
+    };
+    match interval {
+        1 => Some(rule),
+        _ => Some(format!("{};INTERVAL={}", rule, interval)),
+    }
+}
+
+// Days can be abbreviated, like mon or tues
+fn weekday(name: &str) -> Option<&'static str> {
+    let days = [
+        ("monday", "MO"),
+        ("tuesday", "TU"),
+        ("wednesday", "WE"),
+        ("thursday", "TH"),
+        ("friday", "FR"),
+        ("saturday", "SA"),
+        ("sunday", "SU"),
+    ];
+    days.iter()
+        .find(|(full, _)| name.len() >= 2 && full.starts_with(name))
+        .map(|(_, day)| *day)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Long lines continue on the next one, starting with a space, without
+// splitting characters
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_recurring_dates() {
+        assert_eq!(rrule("every day"), Some("FREQ=DAILY".to_string()));
+        assert_eq!(rrule("Daily at 9am"), Some("FREQ=DAILY".to_string()));
+        assert_eq!(
+            rrule("every 2 weeks"),
+            Some("FREQ=WEEKLY;INTERVAL=2".to_string())
+        );
+        assert_eq!(
+            rrule("every other month"),
+            Some("FREQ=MONTHLY;INTERVAL=2".to_string())
+        );
+        assert_eq!(
+            rrule("every mon, fri"),
+            Some("FREQ=WEEKLY;BYDAY=MO,FR".to_string())
+        );
+        assert_eq!(
+            rrule("every tues and thursday"),
+            Some("FREQ=WEEKLY;BYDAY=TU,TH".to_string())
+        );
+        assert_eq!(
+            rrule("every weekday"),
+            Some("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR".to_string())
+        );
+        assert_eq!(rrule("every last day"), None);
+        assert_eq!(rrule("every 3rd friday"), None);
+        assert_eq!(rrule("tomorrow"), None);
+    }
+}
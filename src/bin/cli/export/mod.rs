@@ -0,0 +1,65 @@
+use dothis::api::resource::{CompletedItem, Item};
+
+use super::cache::Cache;
+use super::query::Query;
+
+mod ical;
+mod outline;
+pub mod taskwarrior;
+pub mod template;
+pub mod todotxt;
+
+pub use self::ical::render as ical;
+pub use self::outline::{markdown, org};
+pub use self::taskwarrior::render as taskwarrior;
+pub use self::todotxt::render as todotxt;
+
+// Tasks of the project, if any, that match the query, if any. Completed tasks
+// are only exported to formats that keep them apart.
+pub fn select<'a>(
+    cache: &'a Cache,
+    project: Option<u32>,
+    query: Option<&Query>,
+    completed: bool,
+) -> Vec<&'a Item> {
+    cache
+        .items
+        .iter()
+        .filter(|t| !t.is_deleted && (completed || !t.checked))
+        .filter(|t| project.map_or(true, |id| t.project_id == id))
+        .filter(|t| query.map_or(true, |q| q.matches(t)))
+        .collect()
+}
+
+// Syncs leave completed tasks out, completed/get_all returns them with fewer
+// fields. The time they were added is not among them, their completion stands
+// for it.
+pub fn completed_item(completed: &CompletedItem) -> Item {
+    Item {
+        id: completed.task_id,
+        legacy_id: None,
+        user_id: completed.user_id,
+        project_id: completed.project_id,
+        legay_project_id: None,
+        content: completed.content.clone(),
+        description: String::new(),
+        due: None,
+        priority: 1,
+        parent_id: None,
+        legacy_parent_id: None,
+        child_order: 0,
+        section_id: completed.section_id,
+        day_order: 0,
+        collapsed: false,
+        labels: Vec::new(),
+        added_by_uid: None,
+        assigned_by_uid: None,
+        responsible_uid: None,
+        checked: true,
+        in_history: true,
+        is_deleted: false,
+        sync_id: None,
+        date_completed: Some(completed.completed_date.clone()),
+        date_added: completed.completed_date.clone(),
+    }
+}
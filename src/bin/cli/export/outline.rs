@@ -0,0 +1,226 @@
+use std::collections::BTreeSet as Set;
+
+use dothis::api::resource::{DueDate, Item, Project};
+
+use super::super::cache::Cache;
+
+// Deeper headings are written as the deepest one both formats have
+const MAX_HEADING: usize = 6;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Syntax {
+    Markdown,
+    Org,
+}
+
+// The project tree as a Markdown document, with projects and sections as
+// headings and tasks as nested checkboxes
+pub fn markdown(tasks: &[&Item], cache: &Cache, project: Option<u32>) -> String {
+    render(tasks, cache, project, Syntax::Markdown)
+}
+
+// The same document as Org-mode, with due dates as active timestamps
+pub fn org(tasks: &[&Item], cache: &Cache, project: Option<u32>) -> String {
+    render(tasks, cache, project, Syntax::Org)
+}
+
+struct Outline<'a> {
+    cache: &'a Cache,
+    tasks: &'a [&'a Item],
+    selected: Set<u32>,
+    syntax: Syntax,
+    lines: Vec<String>,
+}
+
+// Only the given tasks are written, along with the projects and sections
+// they belong to. Without a project, every project is written.
+fn render(tasks: &[&Item], cache: &Cache, project: Option<u32>, syntax: Syntax) -> String {
+    let mut outline = Outline {
+        cache: cache,
+        tasks: tasks,
+        selected: tasks.iter().map(|t| t.id).collect(),
+        syntax: syntax,
+        lines: Vec::new(),
+    };
+    match project.and_then(|id| cache.project(id)) {
+        Some(project) => outline.project(project, 1),
+        None => {
+            for project in outline.subprojects(None) {
+                if outline.has_tasks(project.id) {
+                    outline.project(project, 1);
+                }
+            }
+        }
+    }
+
+    let mut output = outline.lines.join("\n");
+    output.push('\n');
+    output
+}
+
+impl<'a> Outline<'a> {
+    fn heading(&mut self, level: usize, title: &str) {
+        let marker = match self.syntax {
+            Syntax::Markdown => "#",
+            Syntax::Org => "*",
+        };
+        if !self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.lines.push(format!(
+            "{} {}",
+            marker.repeat(level.min(MAX_HEADING)),
+            title
+        ));
+        self.lines.push(String::new());
+    }
+
+    fn subprojects(&self, parent_id: Option<u32>) -> Vec<&'a Project> {
+        let mut projects: Vec<&Project> = self
+            .cache
+            .projects
+            .iter()
+            .filter(|p| p.parent_id == parent_id && !p.is_deleted && !p.is_archived)
+            .collect();
+        projects.sort_by_key(|p| p.child_order);
+        projects
+    }
+
+    // Tasks whose parent is not written are written at the top of their
+    // section instead
+    fn top_tasks(&self, project_id: u32, section_id: Option<u32>) -> Vec<&'a Item> {
+        let mut tasks: Vec<&Item> = self
+            .tasks
+            .iter()
+            .filter(|t| t.project_id == project_id && t.section_id == section_id)
+            .filter(|t| t.parent_id.map_or(true, |id| !self.selected.contains(&id)))
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|t| t.child_order);
+        tasks
+    }
+
+    fn has_tasks(&self, project_id: u32) -> bool {
+        self.tasks.iter().any(|t| t.project_id == project_id)
+            || self
+                .subprojects(Some(project_id))
+                .iter()
+                .any(|p| self.has_tasks(p.id))
+    }
+
+    // Sections and subprojects are headings one level below their project,
+    // empty ones are left out
+    fn project(&mut self, project: &Project, level: usize) {
+        self.heading(level, &project.name);
+        for task in self.top_tasks(project.id, None) {
+            self.task(task, 0);
+        }
+
+        let mut sections: Vec<_> = self
+            .cache
+            .sections
+            .iter()
+            .filter(|s| s.project_id == project.id && !s.is_deleted && !s.is_archived)
+            .collect();
+        sections.sort_by_key(|s| s.section_order);
+        for section in sections {
+            let tasks = self.top_tasks(project.id, Some(section.id));
+            if tasks.is_empty() {
+                continue;
+            }
+            self.heading(level + 1, &section.name);
+            for task in tasks {
+                self.task(task, 0);
+            }
+        }
+
+        for subproject in self.subprojects(Some(project.id)) {
+            if self.has_tasks(subproject.id) {
+                self.project(subproject, level + 1);
+            }
+        }
+    }
+
+    // Comments are written under their task, indented like its subtasks
+    fn task(&mut self, task: &Item, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let mut words = vec![
+            match task.checked {
+                true => "- [x]".to_string(),
+                false => "- [ ]".to_string(),
+            },
+            task.content.clone(),
+        ];
+        if let Some(due) = &task.due {
+            words.push(self.due(due));
+        }
+        for label in task.labels.iter().filter_map(|id| self.cache.label(*id)) {
+            words.push(format!("@{}", label.name));
+        }
+        self.lines.push(format!("{}{}", indent, words.join(" ")));
+
+        for note in self
+            .cache
+            .notes
+            .iter()
+            .filter(|n| n.item_id == task.id && !n.is_deleted)
+        {
+            for line in note.content.lines() {
+                match self.syntax {
+                    Syntax::Markdown => self.lines.push(format!("{}  > {}", indent, line)),
+                    Syntax::Org => self.lines.push(format!("{}  {}", indent, line)),
+                }
+            }
+        }
+
+        let mut children: Vec<&Item> = self
+            .tasks
+            .iter()
+            .filter(|t| t.parent_id == Some(task.id))
+            .cloned()
+            .collect();
+        children.sort_by_key(|t| t.child_order);
+        for child in children {
+            self.task(child, depth + 1);
+        }
+    }
+
+    // Recurring due dates keep the string they repeat by
+    fn due(&self, due: &DueDate) -> String {
+        let date = match self.syntax {
+            Syntax::Markdown => format!("(due {})", day_and_time(&due.date, "%Y-%m-%d")),
+            Syntax::Org => format!("<{}>", day_and_time(&due.date, "%Y-%m-%d %a")),
+        };
+        match due.is_recurring {
+            true => format!("{} ({})", date, due.string),
+            false => date,
+        }
+    }
+}
+
+// Todoist dates are full days, floating times or UTC times, like
+// 2020-01-31, 2020-01-31T09:00:00 or 2020-01-31T09:00:00Z. UTC times are
+// written in the local timezone.
+fn day_and_time(date: &str, day_format: &str) -> String {
+    let date = date
+        .get(..19)
+        .map_or(date.to_string(), |d| match date.ends_with('Z') {
+            true => format!("{}Z", d),
+            false => d.to_string(),
+        });
+    let (tm, time_of_day) = match time::strptime(&date, "%Y-%m-%dT%H:%M:%SZ") {
+        Ok(tm) => (time::at(tm.to_timespec()), true),
+        Err(_) => match time::strptime(&date, "%Y-%m-%dT%H:%M:%S") {
+            Ok(tm) => (time::at_utc(tm.to_timespec()), true),
+            Err(_) => match time::strptime(&date, "%Y-%m-%d") {
+                Ok(tm) => (time::at_utc(tm.to_timespec()), false),
+                Err(_) => return date,
+            },
+        },
+    };
+    let format = match time_of_day {
+        true => format!("{} %H:%M", day_format),
+        false => day_format.to_string(),
+    };
+    time::strftime(&format, &tm).unwrap_or(date)
+}
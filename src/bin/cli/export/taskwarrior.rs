@@ -0,0 +1,210 @@
+use std::collections::BTreeSet as Set;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+use dothis::api::resource::Item;
+
+use super::super::cache::Cache;
+
+// Taskwarrior writes dates as UTC timestamps only
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// Exported tasks get uuids made of this prefix and their id, so exporting
+// again updates the tasks Taskwarrior imported instead of duplicating them
+const UUID_PREFIX: u128 = 0x646f_7468_6973_0000_0000_0000_0000_0000;
+
+// A task as written by task export, see
+// https://taskwarrior.org/docs/design/task.html. Other attributes are ignored.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskwarriorTask {
+    pub uuid: String,
+    pub description: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_depends",
+        deserialize_with = "deserialize_depends"
+    )]
+    pub depends: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    pub description: String,
+}
+
+// Taskwarrior 2.6 writes dependencies as an array, earlier versions as a
+// string of comma separated uuids, which both versions read
+fn serialize_depends<S: Serializer>(depends: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&depends.join(","))
+}
+
+fn deserialize_depends<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Depends {
+        List(Vec<String>),
+        Joined(String),
+    }
+    Ok(match Depends::deserialize(deserializer)? {
+        Depends::List(uuids) => uuids,
+        Depends::Joined(uuids) => uuids
+            .split(',')
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect(),
+    })
+}
+
+// Todoist priorities go from 1 (normal) to 4 (urgent), Taskwarrior ones are
+// H, M, L or none
+pub fn priority_of(priority: i32) -> Option<String> {
+    match priority {
+        4 => Some("H".to_string()),
+        3 => Some("M".to_string()),
+        2 => Some("L".to_string()),
+        _ => None,
+    }
+}
+
+pub fn todoist_priority(priority: Option<&str>) -> i32 {
+    match priority {
+        Some("H") => 4,
+        Some("M") => 3,
+        Some("L") => 2,
+        _ => 1,
+    }
+}
+
+pub fn uuid_of(id: u32) -> Uuid {
+    Uuid::from_u128(UUID_PREFIX | id as u128)
+}
+
+// A JSON array of tasks, as task import reads. Projects are written with
+// their parents, separated by dots, and tasks depend on their subtasks.
+pub fn render(tasks: &[&Item], cache: &Cache) -> String {
+    let resolver = cache.resolver();
+    let exported: Set<u32> = tasks.iter().map(|t| t.id).collect();
+
+    let tasks: Vec<TaskwarriorTask> = tasks
+        .iter()
+        .map(|task| {
+            let path = resolver.project_path(task.project_id);
+            TaskwarriorTask {
+                uuid: uuid_of(task.id).to_string(),
+                description: task.content.clone(),
+                status: match task.checked {
+                    true => "completed".to_string(),
+                    false => "pending".to_string(),
+                },
+                entry: to_taskwarrior(&task.date_added),
+                end: task.date_completed.as_deref().and_then(to_taskwarrior),
+                due: task.due.as_ref().and_then(|due| to_taskwarrior(&due.date)),
+                project: match path.is_empty() {
+                    true => None,
+                    false => Some(path.join(".")),
+                },
+                tags: task
+                    .labels
+                    .iter()
+                    .filter_map(|id| cache.label(*id))
+                    .map(|l| l.name.clone())
+                    .collect(),
+                priority: priority_of(task.priority),
+                annotations: cache
+                    .notes
+                    .iter()
+                    .filter(|n| n.item_id == task.id && !n.is_deleted)
+                    .map(|n| Annotation {
+                        entry: to_taskwarrior(&n.posted),
+                        description: n.content.clone(),
+                    })
+                    .collect(),
+                depends: cache
+                    .items
+                    .iter()
+                    .filter(|i| i.parent_id == Some(task.id) && exported.contains(&i.id))
+                    .map(|i| uuid_of(i.id).to_string())
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let mut output = serde_json::to_string_pretty(&tasks).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+// task export writes an array, older versions one task per line, with or
+// without commas between them
+pub fn parse(contents: &str) -> Result<Vec<TaskwarriorTask>, serde_json::Error> {
+    if contents.trim_start().starts_with('[') {
+        return serde_json::from_str(contents);
+    }
+    contents
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+// Todoist dates are full days, floating times or UTC times, like
+// 2020-01-31, 2020-01-31T09:00:00 or 2020-01-31T09:00:00Z. Days and floating
+// times are in the local timezone.
+pub fn to_taskwarrior(date: &str) -> Option<String> {
+    let date = date
+        .get(..19)
+        .map_or(date.to_string(), |d| match date.ends_with('Z') {
+            true => format!("{}Z", d),
+            false => d.to_string(),
+        });
+    let tm = match time::strptime(&date, "%Y-%m-%dT%H:%M:%SZ") {
+        Ok(tm) => tm,
+        Err(_) => {
+            let mut tm = time::strptime(&date, "%Y-%m-%dT%H:%M:%S")
+                .or_else(|_| time::strptime(&date, "%Y-%m-%d"))
+                .ok()?;
+            tm.tm_utcoff = time::now().tm_utcoff;
+            tm.tm_isdst = -1;
+            time::at_utc(tm.to_timespec())
+        }
+    };
+    time::strftime(DATE_FORMAT, &tm).ok()
+}
+
+// Due dates at local midnight are full days, others keep their time in UTC
+pub fn due_date(date: &str) -> Option<String> {
+    let tm = time::strptime(date, DATE_FORMAT).ok()?;
+    let local = time::at(tm.to_timespec());
+    match (local.tm_hour, local.tm_min, local.tm_sec) {
+        (0, 0, 0) => time::strftime("%Y-%m-%d", &local).ok(),
+        _ => time::strftime("%Y-%m-%dT%H:%M:%SZ", &tm).ok(),
+    }
+}
+
+pub fn completion_date(date: &str) -> Option<String> {
+    let tm = time::strptime(date, DATE_FORMAT).ok()?;
+    time::strftime("%Y-%m-%dT%H:%M:%SZ", &tm).ok()
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap as Map;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use dothis::api::resource::Item;
+
+use super::super::cache::Cache;
+
+// Todoist nests tasks at most this deep in templates
+const MAX_INDENT: usize = 5;
+
+// A row of a Todoist template: a section, a task or a note on the task
+// before it. Notes before any task are notes on the project. Columns other
+// than these, like DESCRIPTION in newer templates, are ignored.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Row {
+    #[serde(rename = "TYPE")]
+    pub kind: String,
+    #[serde(rename = "CONTENT")]
+    pub content: String,
+    #[serde(rename = "PRIORITY", default)]
+    pub priority: String,
+    #[serde(rename = "INDENT", default)]
+    pub indent: String,
+    #[serde(rename = "AUTHOR", default)]
+    pub author: String,
+    #[serde(rename = "RESPONSIBLE", default)]
+    pub responsible: String,
+    #[serde(rename = "DATE", default)]
+    pub date: String,
+    #[serde(rename = "DATE_LANG", default)]
+    pub date_lang: String,
+    #[serde(rename = "TIMEZONE", default)]
+    pub timezone: String,
+}
+
+impl Row {
+    // Priorities in templates are as shown by Todoist, from 1 (urgent) to
+    // 4 (normal), the reverse of the API
+    pub fn priority(&self) -> i32 {
+        match self.priority.trim().parse::<i32>() {
+            Ok(priority) if priority >= 1 && priority <= 4 => 5 - priority,
+            _ => 1,
+        }
+    }
+
+    // Top level tasks have an indent of 1
+    pub fn indent(&self) -> usize {
+        self.indent
+            .trim()
+            .parse::<usize>()
+            .unwrap_or(1)
+            .max(1)
+            .min(MAX_INDENT)
+    }
+}
+
+// The tasks of the project as a template, with tasks outside of sections
+// first and the notes of every task after it
+pub fn render(project_id: u32, cache: &Cache) -> Result<String, csv::Error> {
+    let mut rows = Vec::new();
+    let mut sections: Vec<_> = cache
+        .sections
+        .iter()
+        .filter(|s| s.project_id == project_id && !s.is_deleted && !s.is_archived)
+        .collect();
+    sections.sort_by_key(|s| s.section_order);
+
+    task_rows(cache, project_id, None, None, 1, &mut rows);
+    for section in sections {
+        rows.push(Row {
+            kind: "section".to_string(),
+            content: section.name.clone(),
+            ..Row::default()
+        });
+        task_rows(cache, project_id, Some(section.id), None, 1, &mut rows);
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows.iter() {
+        writer.serialize(row)?;
+    }
+    let output = writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(io::Error::from(err.error().kind())))?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+fn task_rows(
+    cache: &Cache,
+    project_id: u32,
+    section_id: Option<u32>,
+    parent_id: Option<u32>,
+    indent: usize,
+    rows: &mut Vec<Row>,
+) {
+    let mut tasks: Vec<&Item> = cache
+        .items
+        .iter()
+        .filter(|i| i.project_id == project_id && i.parent_id == parent_id)
+        .filter(|i| i.section_id == section_id || parent_id.is_some())
+        .filter(|i| !i.checked && !i.is_deleted)
+        .collect();
+    tasks.sort_by_key(|i| i.child_order);
+
+    for task in tasks {
+        rows.push(Row {
+            kind: "task".to_string(),
+            content: task.content.clone(),
+            priority: (5 - task.priority).to_string(),
+            indent: indent.min(MAX_INDENT).to_string(),
+            date: task
+                .due
+                .as_ref()
+                .map_or(String::new(), |d| d.string.clone()),
+            date_lang: task.due.as_ref().map_or(String::new(), |d| d.lang.clone()),
+            timezone: task
+                .due
+                .as_ref()
+                .and_then(|d| d.timezone.clone())
+                .unwrap_or_default(),
+            ..Row::default()
+        });
+        for note in cache
+            .notes
+            .iter()
+            .filter(|n| n.item_id == task.id && !n.is_deleted)
+        {
+            rows.push(Row {
+                kind: "note".to_string(),
+                content: note.content.clone(),
+                ..Row::default()
+            });
+        }
+        task_rows(
+            cache,
+            project_id,
+            section_id,
+            Some(task.id),
+            indent + 1,
+            rows,
+        );
+    }
+}
+
+// Rows without a type separate tasks in templates written by Todoist
+pub fn parse(contents: &str) -> Result<Vec<Row>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let mut rows = Vec::new();
+    for row in reader.deserialize() {
+        let row: Row = row?;
+        if !row.kind.is_empty() {
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+// Replaces placeholders like {{sprint}} with their value. The name of the
+// first placeholder without a value is returned instead.
+pub fn expand(text: &str, vars: &Map<String, String>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = rest[start + 2..end].trim();
+        match vars.get(name) {
+            Some(value) => {
+                expanded.push_str(&rest[..start]);
+                expanded.push_str(value);
+            }
+            None => return Err(name.to_string()),
+        }
+        rest = &rest[end + 2..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
@@ -0,0 +1,175 @@
+use dothis::api::resource::Item;
+
+use super::super::cache::Cache;
+
+// A task as written in todo.txt, see https://github.com/todotxt/todo.txt
+#[derive(Debug, Default)]
+pub struct TodoTxtTask {
+    pub completed: bool,
+    pub priority: Option<char>,
+    pub completion_date: Option<String>,
+    pub creation_date: Option<String>,
+    // The description without projects, contexts and the due date
+    pub content: String,
+    pub projects: Vec<String>,
+    pub contexts: Vec<String>,
+    pub due: Option<String>,
+}
+
+// Todoist priorities go from 1 (normal) to 4 (urgent), todo.txt ones from A
+// (highest) to Z, with no priority being the lowest
+pub fn priority_of(priority: i32) -> Option<char> {
+    match priority {
+        4 => Some('A'),
+        3 => Some('B'),
+        2 => Some('C'),
+        _ => None,
+    }
+}
+
+pub fn todoist_priority(priority: Option<char>) -> i32 {
+    match priority {
+        Some('A') => 4,
+        Some('B') => 3,
+        Some('C') => 2,
+        _ => 1,
+    }
+}
+
+// One line per task, with its project and labels. Completed tasks keep their
+// priority as pri:, as todo.txt drops it from completed lines.
+pub fn render(tasks: &[&Item], cache: &Cache) -> String {
+    let mut output = String::new();
+    for task in tasks.iter() {
+        let mut words = Vec::new();
+        if task.checked {
+            words.push("x".to_string());
+            if let Some(completed) = &task.date_completed {
+                words.push(day(completed).to_string());
+            }
+        } else if let Some(priority) = priority_of(task.priority) {
+            words.push(format!("({})", priority));
+        }
+        words.push(day(&task.date_added).to_string());
+        words.push(task.content.clone());
+
+        if let Some(project) = cache.project(task.project_id) {
+            words.push(format!("+{}", tag(&project.name)));
+        }
+        for label in task.labels.iter().filter_map(|id| cache.label(*id)) {
+            words.push(format!("@{}", tag(&label.name)));
+        }
+        if let Some(due) = &task.due {
+            words.push(format!("due:{}", day(&due.date)));
+        }
+        if task.checked {
+            if let Some(priority) = priority_of(task.priority) {
+                words.push(format!("pri:{}", priority));
+            }
+        }
+
+        output.push_str(&words.join(" "));
+        output.push('\n');
+    }
+    output
+}
+
+// Blank lines are not tasks
+pub fn parse(line: &str) -> Option<TodoTxtTask> {
+    let mut task = TodoTxtTask::default();
+    let mut words = line.split_whitespace().peekable();
+
+    if words.peek() == Some(&"x") {
+        words.next();
+        task.completed = true;
+        if words.peek().map_or(false, |w| is_date(w)) {
+            task.completion_date = words.next().map(|w| w.to_string());
+        }
+    } else if let Some(priority) = words.peek().and_then(|w| parse_priority(w)) {
+        words.next();
+        task.priority = Some(priority);
+    }
+    if words.peek().map_or(false, |w| is_date(w)) {
+        task.creation_date = words.next().map(|w| w.to_string());
+    }
+
+    let mut content = Vec::new();
+    for word in words {
+        if word.len() > 1 && word.starts_with('+') {
+            task.projects.push(word[1..].to_string());
+        } else if word.len() > 1 && word.starts_with('@') {
+            task.contexts.push(word[1..].to_string());
+        } else if word.starts_with("due:") && is_date(&word[4..]) {
+            task.due = Some(word[4..].to_string());
+        } else if word.starts_with("pri:") && task.priority.is_none() {
+            task.priority = parse_priority(&format!("({})", &word[4..]));
+        } else {
+            content.push(word);
+        }
+    }
+    task.content = content.join(" ");
+
+    match task.content.is_empty() {
+        true => None,
+        false => Some(task),
+    }
+}
+
+fn parse_priority(word: &str) -> Option<char> {
+    let chars: Vec<char> = word.chars().collect();
+    match chars.as_slice() {
+        ['(', priority, ')'] if priority.is_ascii_uppercase() => Some(*priority),
+        _ => None,
+    }
+}
+
+fn is_date(word: &str) -> bool {
+    time::strptime(word, "%Y-%m-%d").is_ok() && word.len() == 10
+}
+
+// Dates and times are written as dates only
+fn day(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+// Projects and contexts end at the first space
+fn tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<&str>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_open_tasks() {
+        let task = parse("(A) 2020-01-30 Call mom +Family @phone due:2020-02-01").unwrap();
+        assert!(!task.completed);
+        assert_eq!(task.priority, Some('A'));
+        assert_eq!(task.creation_date.as_deref(), Some("2020-01-30"));
+        assert_eq!(task.content, "Call mom");
+        assert_eq!(task.projects, vec!["Family"]);
+        assert_eq!(task.contexts, vec!["phone"]);
+        assert_eq!(task.due.as_deref(), Some("2020-02-01"));
+    }
+
+    #[test]
+    fn parses_completed_tasks() {
+        let task = parse("x 2020-02-02 2020-01-30 Pay rent pri:B").unwrap();
+        assert!(task.completed);
+        assert_eq!(task.completion_date.as_deref(), Some("2020-02-02"));
+        assert_eq!(task.creation_date.as_deref(), Some("2020-01-30"));
+        assert_eq!(task.priority, Some('B'));
+        assert_eq!(task.content, "Pay rent");
+    }
+
+    #[test]
+    fn keeps_words_that_only_look_like_tags() {
+        let task = parse("(a) Email me@example.com + due:soon").unwrap();
+        assert_eq!(task.priority, None);
+        assert_eq!(task.content, "(a) Email me@example.com + due:soon");
+        assert!(task.contexts.is_empty());
+        assert!(parse("   ").is_none());
+        assert!(parse("x +Project @context").is_none());
+    }
+}
@@ -0,0 +1,26 @@
+use std::io::Write;
+use std::process;
+
+use serde_json::Value;
+
+use super::error::DothisError;
+
+// Runs a shell command for an event, with the resource it is about as JSON on
+// stdin and the event in DOTHIS_EVENT, like item:completed
+pub fn run(command: &str, event: &str, payload: &Value) -> Result<(), DothisError> {
+    let mut child = process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("DOTHIS_EVENT", event)
+        .stdin(process::Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(serde_json::to_string(payload)?.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(DothisError::HookError(command.to_string())),
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::BTreeMap as Map;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use dothis::api::client::CommandResult;
+use dothis::api::resource::{DueDate, Item, Project, RawCommand};
+
+use super::cache::Cache;
+use super::error::DothisError;
+
+// Older entries are dropped, undoing that far back is unlikely to work anyway
+const MAX_ENTRIES: usize = 100;
+
+// Local record of the write commands run with a profile, with the commands
+// that revert them. Inverse commands are built from snapshots of the cache
+// taken before the change, so they restore what dothis knew at the time.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub date: String,
+    pub description: String,
+    pub undo: Vec<RawCommand>,
+}
+
+impl Journal {
+    fn path(profile: &str) -> Result<PathBuf, DothisError> {
+        match dirs::data_dir() {
+            Some(dir) => Ok(dir
+                .join("dothis")
+                .join("history")
+                .join(format!("{}.json", profile))),
+            None => Err(DothisError::MissingConfigDir),
+        }
+    }
+
+    // Unlike the cache, a journal that cannot be read is an error, as
+    // replacing it would lose the history
+    pub fn load(profile: &str) -> Result<Journal, DothisError> {
+        let path = Journal::path(profile)?;
+        let mut journal = match path.exists() {
+            true => serde_json::from_str(&fs::read_to_string(&path)?)?,
+            false => Journal::default(),
+        };
+        journal.path = path;
+        Ok(journal)
+    }
+
+    pub fn save(&self) -> Result<(), DothisError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, description: &str, undo: Vec<RawCommand>) -> Result<(), DothisError> {
+        if undo.is_empty() {
+            return Ok(());
+        }
+        self.entries.push(Entry {
+            date: time::strftime("%Y-%m-%dT%H:%M:%SZ", &time::now_utc()).unwrap_or_default(),
+            description: description.to_string(),
+            undo: undo,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.save()
+    }
+
+    // Bulk commands only record the inverses of the commands that succeeded
+    pub fn record_batch(
+        &mut self,
+        description: &str,
+        results: &[CommandResult],
+        undo: Vec<Vec<RawCommand>>,
+    ) -> Result<(), DothisError> {
+        let succeeded = results
+            .iter()
+            .zip(undo.into_iter())
+            .filter(|(result, _)| result.is_ok())
+            .flat_map(|(_, inverses)| inverses.into_iter())
+            .collect();
+        self.record(description, succeeded)
+    }
+
+    // Most recent first
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn last(&self) -> Option<&Entry> {
+        self.entries.last()
+    }
+
+    pub fn pop(&mut self) -> Option<Entry> {
+        self.entries.pop()
+    }
+
+    // Keeps the commands of the last entry that are left to undo
+    pub fn retain_last(&mut self, undo: Vec<RawCommand>) {
+        if let Some(entry) = self.entries.last_mut() {
+            entry.undo = undo;
+        }
+    }
+}
+
+// Singular name of a resource type, as used by sync API commands
+pub fn kind_of(resource_type: &str) -> &str {
+    match resource_type {
+        "tasks" => "item",
+        "projects" => "project",
+        "labels" => "label",
+        "notes" => "note",
+        "sections" => "section",
+        "project_notes" => "project_note",
+        other => other,
+    }
+}
+
+// Builds the commands that revert others, kept as JSON as they were built.
+// Resources added back get a temp_id, so later commands of the same entry can
+// refer to them.
+pub struct Inverse;
+
+impl Inverse {
+    // Reverts adding a resource of the given type
+    pub fn delete(resource_type: &str, id: u32) -> Vec<RawCommand> {
+        vec![RawCommand::new(
+            &format!("{}_delete", kind_of(resource_type)),
+            json!({ "id": id }),
+        )]
+    }
+
+    // Closing a recurring task moves its due date instead of completing it
+    pub fn uncomplete(cache: &Cache, id: u32) -> Vec<RawCommand> {
+        match cache.item(id) {
+            Some(item) if item.due.as_ref().map_or(false, |d| d.is_recurring) => {
+                Inverse::reschedule(cache, id)
+            }
+            _ => vec![RawCommand::new("item_uncomplete", json!({ "id": id }))],
+        }
+    }
+
+    pub fn reschedule(cache: &Cache, id: u32) -> Vec<RawCommand> {
+        match cache.item(id) {
+            Some(item) => vec![RawCommand::new(
+                "item_update",
+                json!({ "id": id, "due": due_json(&item.due) }),
+            )],
+            None => Vec::new(),
+        }
+    }
+
+    pub fn assign(cache: &Cache, id: u32) -> Vec<RawCommand> {
+        match cache.item(id) {
+            Some(item) => vec![RawCommand::new(
+                "item_update",
+                json!({ "id": id, "responsible_uid": item.responsible_uid }),
+            )],
+            None => Vec::new(),
+        }
+    }
+
+    // Reverts an edit by updating every editable field to its previous value
+    pub fn restore(cache: &Cache, resource_type: &str, id: u32) -> Vec<RawCommand> {
+        match resource_type {
+            "tasks" => cache.item(id).map_or(Vec::new(), restore_item),
+            "projects" => cache.project(id).map_or(Vec::new(), |project| {
+                vec![
+                    RawCommand::new(
+                        "project_update",
+                        json!({
+                            "id": id,
+                            "name": project.name,
+                            "color": project.color,
+                            "is_favorite": project.is_favorite as i32,
+                        }),
+                    ),
+                    RawCommand::new(
+                        "project_move",
+                        json!({ "id": id, "parent_id": project.parent_id }),
+                    ),
+                ]
+            }),
+            "labels" => cache.label(id).map_or(Vec::new(), |label| {
+                vec![RawCommand::new(
+                    "label_update",
+                    json!({
+                        "id": id,
+                        "name": label.name,
+                        "color": label.color,
+                        "is_favorite": label.is_favorite as i32,
+                    }),
+                )]
+            }),
+            "notes" => cache.note(id).map_or(Vec::new(), |note| {
+                vec![RawCommand::new(
+                    "note_update",
+                    json!({ "id": id, "content": note.content }),
+                )]
+            }),
+            _ => Vec::new(),
+        }
+    }
+
+    // Reverts deleting a resource by adding it back, with everything deleted
+    // along with it. Resources added back get new ids.
+    pub fn readd(cache: &Cache, resource_type: &str, id: u32) -> Vec<RawCommand> {
+        let mut inverses = Vec::new();
+        match resource_type {
+            "tasks" => {
+                if let Some(item) = cache.item(id) {
+                    readd_item(
+                        cache,
+                        item,
+                        json!(item.project_id),
+                        json!(item.section_id),
+                        json!(item.parent_id),
+                        &mut inverses,
+                    );
+                }
+            }
+            "projects" => {
+                if let Some(project) = cache.project(id) {
+                    readd_project(cache, project, json!(project.parent_id), &mut inverses);
+                }
+            }
+            "labels" => {
+                if let Some(label) = cache.label(id) {
+                    let temp_id = Uuid::new_v4();
+                    inverses.push(RawCommand::add(
+                        "label_add",
+                        json!({
+                            "name": label.name,
+                            "color": label.color,
+                            "item_order": label.item_order,
+                            "is_favorite": label.is_favorite as i32,
+                        }),
+                        temp_id,
+                    ));
+                    // Tasks lose deleted labels, so they get the new one instead
+                    for item in cache.items.iter().filter(|i| i.labels.contains(&id)) {
+                        let labels: Vec<Value> = item
+                            .labels
+                            .iter()
+                            .map(|l| match *l == id {
+                                true => json!(temp_id),
+                                false => json!(l),
+                            })
+                            .collect();
+                        inverses.push(RawCommand::new(
+                            "item_update",
+                            json!({ "id": item.id, "labels": labels }),
+                        ));
+                    }
+                }
+            }
+            "notes" => {
+                if let Some(note) = cache.note(id) {
+                    inverses.push(RawCommand::add(
+                        "note_add",
+                        json!({ "item_id": note.item_id, "content": note.content }),
+                        Uuid::new_v4(),
+                    ));
+                }
+            }
+            _ => (),
+        }
+        inverses
+    }
+}
+
+// Due dates are sent back with the string they were created from, so
+// recurring tasks keep recurring
+fn due_json(due: &Option<DueDate>) -> Value {
+    match due {
+        Some(due) => json!({
+            "date": due.date,
+            "timezone": due.timezone,
+            "string": due.string,
+            "lang": due.lang,
+        }),
+        None => Value::Null,
+    }
+}
+
+// Tasks can only be moved to one of a parent, section or project at a time
+fn restore_item(item: &Item) -> Vec<RawCommand> {
+    let destination = match (item.parent_id, item.section_id) {
+        (Some(parent_id), _) => json!({ "id": item.id, "parent_id": parent_id }),
+        (None, Some(section_id)) => json!({ "id": item.id, "section_id": section_id }),
+        (None, None) => json!({ "id": item.id, "project_id": item.project_id }),
+    };
+    vec![
+        RawCommand::new(
+            "item_update",
+            json!({
+                "id": item.id,
+                "content": item.content,
+                "description": item.description,
+                "due": due_json(&item.due),
+                "priority": item.priority,
+                "labels": item.labels,
+            }),
+        ),
+        RawCommand::new("item_move", destination),
+    ]
+}
+
+// Subtasks and notes are deleted with their task
+fn readd_item(
+    cache: &Cache,
+    item: &Item,
+    project_id: Value,
+    section_id: Value,
+    parent_id: Value,
+    inverses: &mut Vec<RawCommand>,
+) {
+    let temp_id = Uuid::new_v4();
+    inverses.push(RawCommand::add(
+        "item_add",
+        json!({
+            "content": item.content,
+            "description": item.description,
+            "project_id": project_id,
+            "section_id": section_id,
+            "parent_id": parent_id,
+            "due": due_json(&item.due),
+            "priority": item.priority,
+            "labels": item.labels,
+            "child_order": item.child_order,
+        }),
+        temp_id,
+    ));
+    for note in cache.notes.iter().filter(|n| n.item_id == item.id) {
+        inverses.push(RawCommand::add(
+            "note_add",
+            json!({ "item_id": temp_id, "content": note.content }),
+            Uuid::new_v4(),
+        ));
+    }
+    for child in cache.items.iter().filter(|i| i.parent_id == Some(item.id)) {
+        readd_item(
+            cache,
+            child,
+            project_id.clone(),
+            section_id.clone(),
+            json!(temp_id),
+            inverses,
+        );
+    }
+}
+
+// Sections, tasks and subprojects are deleted with their project
+fn readd_project(
+    cache: &Cache,
+    project: &Project,
+    parent_id: Value,
+    inverses: &mut Vec<RawCommand>,
+) {
+    let temp_id = Uuid::new_v4();
+    inverses.push(RawCommand::add(
+        "project_add",
+        json!({
+            "name": project.name,
+            "color": project.color,
+            "parent_id": parent_id,
+            "child_order": project.child_order,
+            "is_favorite": project.is_favorite as i32,
+        }),
+        temp_id,
+    ));
+
+    let mut sections = Map::new();
+    for section in cache.sections.iter().filter(|s| s.project_id == project.id) {
+        let section_temp_id = Uuid::new_v4();
+        sections.insert(section.id, section_temp_id);
+        inverses.push(RawCommand::add(
+            "section_add",
+            json!({
+                "name": section.name,
+                "project_id": temp_id,
+                "section_order": section.section_order,
+            }),
+            section_temp_id,
+        ));
+    }
+
+    for item in cache
+        .items
+        .iter()
+        .filter(|i| i.project_id == project.id && i.parent_id.is_none())
+    {
+        let section_id = item
+            .section_id
+            .and_then(|id| sections.get(&id))
+            .map_or(Value::Null, |temp_id| json!(temp_id));
+        readd_item(
+            cache,
+            item,
+            json!(temp_id),
+            section_id,
+            Value::Null,
+            inverses,
+        );
+    }
+
+    for child in cache
+        .projects
+        .iter()
+        .filter(|p| p.parent_id == Some(project.id))
+    {
+        readd_project(cache, child, json!(temp_id), inverses);
+    }
+}
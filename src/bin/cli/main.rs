@@ -1,18 +1,59 @@
+use std::path::Path;
 use std::process;
 
 extern crate clap;
-use clap::{App, Arg, ArgMatches};
+use clap::ArgMatches;
 
 #[macro_use]
 extern crate prettytable;
 use prettytable::Table;
 
+mod app;
+use app::build_app;
+
+mod archive;
+use archive::Archive;
+
+mod cache;
+use cache::Cache;
+
 mod command;
-use command::{AddCommand, Command, ListCommand};
+use command::{
+    api_date, duration_of, id_of, read_lines, resolve_arg, AddCommand, AnswerInvitationCommand,
+    ApplyTemplateCommand, AssignCommand, BackupCommand, Command, CompleteCommand,
+    CompletionsCommand, ConfigCommand, DaemonCommand, DoneCommand, EditCommand, ExportCommand,
+    ExportTemplateCommand, GitBranchCommand, GitHookInstallCommand, GitPostCommitCommand,
+    GitTodoScanCommand, HistoryCommand, ImportCommand, ImportLinesCommand, ListCommand, Listen,
+    LogCommand, LoginCommand, NotificationsCommand, ReadNotificationsCommand, RemoveCommand,
+    RescheduleCommand, RestoreCommand, ServeIcalCommand, ShareCommand, ShowCommand, StatsCommand,
+    UndoCommand, WatchCommand, WebhookServeCommand, WebhookSignCommand,
+};
+
+mod config;
+use config::{Config, Profile};
+
+mod credentials;
+
+mod dry_run;
+
+mod export;
+
+mod hook;
 
 mod error;
 use crate::error::DothisError;
 
+mod journal;
+use journal::Journal;
+
+mod picker;
+
+mod query;
+use query::Query;
+
+use dothis::api::client::{TodoistApiError, TodoistClient};
+use dothis::api::resource::ActivityQuery;
+
 fn main() {
     env_logger::init();
     let dothis = Dothis::new();
@@ -27,120 +68,621 @@ fn main() {
 }
 
 struct Dothis<'a> {
-    token: String,
-    resource: String,
-    command: String,
+    token: Option<String>,
+    profile: Option<String>,
+    dry_run: bool,
     args: ArgMatches<'a>,
 }
 
 impl<'a> Dothis<'a> {
     fn new() -> Self {
-        let matches = App::new("dothis")
-            .version("1.0")
-            .author("Tomas Farias")
-            .about("dothis the CLI for Todoist")
-            .arg(
-                Arg::with_name("token")
-                    .required(true)
-                    .takes_value(true)
-                    .require_equals(true)
-                    .long("token")
-                    .short("t")
-                    .env("TODOIST_API_TOKEN")
-                    .help("Todoist API token, not required if TODOIST_API_TOKEN environment variable is set")
-            )
-            .arg(
-                Arg::with_name("command")
-                    .takes_value(true)
-                    .index(1)
-                    .required(true)
-                    .default_value("list")
-                    .help("command to execute")
-            )
-            .arg(
-                Arg::with_name("resource")
-                    .takes_value(true)
-                    .index(2)
-                    .required(true)
-                    .default_value_if("command", Some("list"), "tasks")
-                    .help("resource to operate on")
-            )
-            .arg(
-                Arg::with_name("name")
-                    .takes_value(true)
-                    .help("resource name")
-                    .requires_ifs(&[
-                        ("command", "add"),
-                        ("resource", "projects"),
-                    ])
-            )
-            .arg(
-                Arg::with_name("color")
-                    .short("c")
-                    .takes_value(true)
-                    .help("resource color string")
-            )
-            .arg(
-                Arg::with_name("favorite")
-                    .long("favorite")
-                    .help("mark resource as favorite")
-            )
-            .arg(
-                Arg::with_name("content")
-                    .takes_value(true)
-                    .help("resource content")
-                    .requires_ifs(&[
-                        ("command", "add"),
-                        ("resource", "tasks")
-                    ])
-            )
-            .get_matches();
-
-        let token = matches
-            .value_of("token")
-            .expect("argument token is required");
-        let command = matches
-            .value_of("command")
-            .expect("argument command is required");
-        let resource = matches
-            .value_of("resource")
-            .expect("argument resource is required");
+        let matches = build_app().get_matches();
 
         Dothis {
-            token: token.to_string(),
-            command: command.to_string(),
-            resource: resource.to_string(),
+            token: matches.value_of("token").map(|t| t.to_string()),
+            profile: matches.value_of("profile").map(|p| p.to_string()),
+            dry_run: matches.is_present("dry-run"),
             args: matches,
         }
     }
 
     fn run(&self) -> Result<(), DothisError> {
-        let resource_type = self.get_resource_type()?;
+        let config = Config::load()?;
+        let profile_name = config.profile_name(self.profile.as_deref());
+
+        // Commands that do not talk to the API
+        match self.args.subcommand() {
+            ("config", Some(args)) => {
+                return ConfigCommand::new(config, &profile_name, args.clone()).execute()
+            }
+            ("login", Some(_)) => return LoginCommand::new(&profile_name).execute(),
+            ("completions", Some(args)) => {
+                let shell = args.value_of("shell").expect("argument shell is required");
+                return CompletionsCommand::new(shell).execute();
+            }
+            ("history", Some(_)) => {
+                return HistoryCommand::new(Journal::load(&profile_name)?).execute()
+            }
+            ("webhook", Some(args)) => {
+                let profile = config.profile(self.profile.as_deref())?;
+                return self.run_webhook(args, &profile_name, profile);
+            }
+            ("git", Some(args)) if args.subcommand_name() == Some("hook") => {
+                let force = args
+                    .subcommand_matches("hook")
+                    .and_then(|hook| hook.subcommand_matches("install"))
+                    .map_or(false, |install| install.is_present("force"));
+                return GitHookInstallCommand::new(self.profile.as_deref(), force).execute();
+            }
+            ("_complete", Some(args)) => {
+                let resource = args
+                    .value_of("resource")
+                    .expect("argument resource is required");
+                return CompleteCommand::new(Cache::load(&profile_name)?, resource).execute();
+            }
+            _ => (),
+        }
+
+        let profile = config.profile(self.profile.as_deref())?;
+        let mut client = self.get_client(&profile_name, &profile)?;
+        client.set_dry_run(self.dry_run);
+        let url = client.url().to_string();
+
+        // Write commands stop at sending their query when running dry, the
+        // cache they saved is what the query is compared to
+        match self.run_with_client(client, &profile_name, &profile) {
+            Err(DothisError::ApiError(TodoistApiError::DryRun(query))) => {
+                dry_run::report(&url, &query, &Cache::load(&profile_name)?)
+            }
+            result => result,
+        }
+    }
+
+    // Webhooks are signed with the client secret of an app, not the API token
+    fn run_webhook(
+        &self,
+        args: &ArgMatches,
+        profile_name: &str,
+        profile: Profile,
+    ) -> Result<(), DothisError> {
+        let (command, command_args) = match args.subcommand() {
+            (command, Some(command_args)) => (command, command_args),
+            _ => return Err(DothisError::MissingCommand),
+        };
+        let secret = match command_args
+            .value_of("secret")
+            .or(profile.webhook_secret.as_deref())
+        {
+            Some(secret) => secret.to_string(),
+            None => return Err(DothisError::MissingWebhookSecret),
+        };
+
+        match command {
+            "serve" => {
+                let address = format!(
+                    "{}:{}",
+                    command_args.value_of("address").unwrap_or("127.0.0.1"),
+                    command_args.value_of("port").unwrap_or("8080")
+                );
+                WebhookServeCommand::new(
+                    Cache::load(profile_name)?,
+                    &address,
+                    &secret,
+                    profile.hooks,
+                )
+                .execute()
+            }
+            "sign" => WebhookSignCommand::new(&secret, command_args.value_of("file")).execute(),
+            _ => Err(DothisError::UnknownCommand),
+        }
+    }
+
+    fn run_with_client(
+        &self,
+        client: TodoistClient,
+        profile_name: &str,
+        profile: &Profile,
+    ) -> Result<(), DothisError> {
+        let mut cache = Cache::load(profile_name)?;
+
+        match self.args.subcommand() {
+            ("list", args) => {
+                let resource = args
+                    .and_then(|a| a.value_of("resource"))
+                    .or(profile.default_view.as_deref())
+                    .unwrap_or("tasks");
+                let project = match args {
+                    Some(args) => resolve_arg(&client, &mut cache, args, "projects", "project")?,
+                    None => None,
+                };
+                let query = match args {
+                    Some(args) => self.get_query(&client, &mut cache, args)?,
+                    None => None,
+                };
+                let mut list = ListCommand::new(
+                    client,
+                    cache,
+                    self.get_resource_type(resource)?,
+                    Table::new(),
+                    project,
+                    query,
+                    profile.date_format.as_deref(),
+                    profile.color_theme.as_deref(),
+                    args.and_then(|a| a.value_of("output")),
+                    args.and_then(|a| a.value_of("since")).and_then(api_date),
+                    args.and_then(|a| a.value_of("until")).and_then(api_date),
+                    args.and_then(|a| a.value_of("assignee")),
+                    args.map_or(false, |a| a.is_present("description")),
+                );
+
+                list.execute()
+            }
+            ("add", Some(args)) => {
+                let (resource, add_args) = self.get_resource_args(args)?;
+                let mut add = AddCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    resource,
+                    add_args.clone(),
+                    profile.default_project.as_deref(),
+                );
+
+                add.execute()
+            }
+            ("edit", Some(args)) => {
+                let (resource, edit_args) = self.get_resource_args(args)?;
+                let picked = match (resource, edit_args.value_of(resource.trim_end_matches('s'))) {
+                    ("tasks", None) | ("projects", None) => {
+                        Some(self.get_id(&client, &mut cache, resource, edit_args, false)?)
+                    }
+                    _ => None,
+                };
+                EditCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    resource,
+                    edit_args.clone(),
+                    picked,
+                )
+                .execute()
+            }
+            // Moving a task is an edit of where it is only
+            ("mv", Some(args)) => {
+                let task = self.get_id(&client, &mut cache, "tasks", args, false)?;
+                EditCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    "tasks",
+                    args.clone(),
+                    Some(task),
+                )
+                .execute()
+            }
+            ("done", Some(args)) => {
+                let tasks = self.get_task_ids(&client, &mut cache, args)?;
+                DoneCommand::new(client, cache, Journal::load(profile_name)?, tasks).execute()
+            }
+            ("reschedule", Some(args)) => {
+                let tasks = self.get_task_ids(&client, &mut cache, args)?;
+                let due = args.value_of("due").expect("argument due is required");
+                RescheduleCommand::new(client, cache, Journal::load(profile_name)?, tasks, due)
+                    .execute()
+            }
+            ("assign", Some(args)) => {
+                let task = cache.resolve(
+                    &client,
+                    "tasks",
+                    args.value_of("task").expect("argument task is required"),
+                )?;
+                AssignCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    task,
+                    args.value_of("assignee"),
+                )
+                .execute()
+            }
+            ("share", Some(args)) => {
+                let project = resolve_arg(&client, &mut cache, args, "projects", "project")?
+                    .expect("argument project is required");
+                let emails = args
+                    .values_of("email")
+                    .map_or(Vec::new(), |v| v.map(|e| e.to_string()).collect());
+                ShareCommand::new(client, cache, project, emails, args.is_present("remove"))
+                    .execute()
+            }
+            ("import-lines", Some(args)) => ImportLinesCommand::new(
+                client,
+                cache,
+                Journal::load(profile_name)?,
+                args.clone(),
+                profile.default_project.as_deref(),
+            )
+            .execute(),
+            ("rm", Some(args)) => {
+                let (resource, rm_args) = self.get_resource_args(args)?;
+                let id = self.get_id(&client, &mut cache, resource, rm_args, true)?;
+                RemoveCommand::new(client, cache, Journal::load(profile_name)?, resource, id)
+                    .execute()
+            }
+            ("show", Some(args)) => {
+                let (resource, show_args) = self.get_resource_args(args)?;
+                let id = self.get_id(&client, &mut cache, resource, show_args, false)?;
+                ShowCommand::new(client, cache, resource, id, profile.date_format.as_deref())
+                    .execute()
+            }
+            ("export", Some(args)) => {
+                let (format, export_args) = match args.subcommand() {
+                    (format, Some(export_args)) => (format, export_args),
+                    _ => return Err(DothisError::UnknownCommand),
+                };
+                let project = resolve_arg(&client, &mut cache, export_args, "projects", "project")?;
+                let query = self.get_query(&client, &mut cache, export_args)?;
+                ExportCommand::new(client, cache, format, export_args.clone(), project, query)
+                    .execute()
+            }
+            ("import", Some(args)) => {
+                let (format, import_args) = match args.subcommand() {
+                    (format, Some(import_args)) => (format, import_args),
+                    _ => return Err(DothisError::UnknownCommand),
+                };
+                ImportCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    format,
+                    import_args.clone(),
+                    profile.default_project.as_deref(),
+                )
+                .execute()
+            }
+            ("template", Some(args)) => match args.subcommand() {
+                ("export", Some(template_args)) => {
+                    let project = template_args
+                        .value_of("project")
+                        .expect("argument project is required");
+                    let project = cache.resolve(&client, "projects", project)?;
+                    ExportTemplateCommand::new(client, cache, project).execute()
+                }
+                ("apply", Some(template_args)) => {
+                    let into = template_args
+                        .value_of("into")
+                        .expect("argument into is required");
+                    let project = cache.resolve(&client, "projects", into)?;
+                    // Values are validated as name=value by clap
+                    let vars = template_args
+                        .values_of("var")
+                        .map_or(Vec::new(), |v| v.collect())
+                        .into_iter()
+                        .filter_map(|var| {
+                            let mut parts = var.splitn(2, '=');
+                            match (parts.next(), parts.next()) {
+                                (Some(name), Some(value)) => {
+                                    Some((name.trim().to_string(), value.to_string()))
+                                }
+                                _ => None,
+                            }
+                        })
+                        .collect();
+                    ApplyTemplateCommand::new(
+                        client,
+                        Journal::load(profile_name)?,
+                        template_args
+                            .value_of("file")
+                            .expect("argument file is required"),
+                        project,
+                        vars,
+                    )
+                    .execute()
+                }
+                _ => Err(DothisError::UnknownCommand),
+            },
+            ("stats", Some(_)) => StatsCommand::new(client).execute(),
+            ("watch", Some(args)) => {
+                let query = self.get_query(&client, &mut cache, args)?;
+                let interval = args
+                    .value_of("interval")
+                    .and_then(duration_of)
+                    .expect("argument interval has a default");
+                WatchCommand::new(
+                    client,
+                    cache,
+                    query,
+                    interval,
+                    args.is_present("json"),
+                    args.value_of("exec"),
+                )
+                .execute()
+            }
+            ("git", Some(args)) => match args.subcommand() {
+                ("post-commit", Some(_)) => {
+                    GitPostCommitCommand::new(client, cache, Journal::load(profile_name)?).execute()
+                }
+                ("branch", Some(branch_args)) => {
+                    let task = cache.resolve(
+                        &client,
+                        "tasks",
+                        branch_args
+                            .value_of("task")
+                            .expect("argument task is required"),
+                    )?;
+                    GitBranchCommand::new(cache, task).execute()
+                }
+                ("todo-scan", Some(scan_args)) => {
+                    let project = match scan_args
+                        .value_of("project")
+                        .or(profile.todo_project.as_deref())
+                    {
+                        Some(project) => cache.resolve(&client, "projects", project)?,
+                        None => return Err(DothisError::MissingTodoProject),
+                    };
+                    let paths = scan_args
+                        .values_of("path")
+                        .map_or(Vec::new(), |p| p.collect());
+                    GitTodoScanCommand::new(
+                        client,
+                        cache,
+                        Journal::load(profile_name)?,
+                        project,
+                        paths,
+                    )
+                    .execute()
+                }
+                _ => Err(DothisError::UnknownCommand),
+            },
+            ("daemon", Some(args)) => {
+                let listen = match args.value_of("socket") {
+                    Some(path) => Listen::Socket(path.to_string()),
+                    None => Listen::Http(format!(
+                        "{}:{}",
+                        args.value_of("address").unwrap_or("127.0.0.1"),
+                        args.value_of("port").expect("socket or port is required")
+                    )),
+                };
+                let interval = args
+                    .value_of("interval")
+                    .and_then(duration_of)
+                    .expect("argument interval has a default");
+                DaemonCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    profile_name,
+                    listen,
+                    interval,
+                    profile.default_project.as_deref(),
+                )
+                .execute()
+            }
+            ("notifications", Some(args)) => match args.subcommand() {
+                ("read", Some(read_args)) => {
+                    let ids = read_args.values_of("id").map_or(Vec::new(), |v| {
+                        v.filter_map(|id| id.parse::<u32>().ok()).collect()
+                    });
+                    ReadNotificationsCommand::new(client, ids).execute()
+                }
+                (answer, Some(answer_args)) if answer == "accept" || answer == "reject" => {
+                    let id = id_of(answer_args, "id").expect("argument id is required");
+                    AnswerInvitationCommand::new(client, cache, id, answer == "accept").execute()
+                }
+                _ => NotificationsCommand::new(
+                    client,
+                    cache,
+                    args.is_present("unread"),
+                    profile.date_format.as_deref(),
+                )
+                .execute(),
+            },
+            ("log", Some(args)) => {
+                let query = ActivityQuery {
+                    object_type: args.value_of("object-type").map(|t| t.to_string()),
+                    event_type: args.value_of("event").map(|e| e.to_string()),
+                    parent_project_id: resolve_arg(
+                        &client, &mut cache, args, "projects", "project",
+                    )?,
+                    ..ActivityQuery::default()
+                };
+                LogCommand::new(
+                    client,
+                    cache,
+                    query,
+                    args.value_of("since").and_then(api_date),
+                    args.is_present("follow"),
+                    profile.date_format.as_deref(),
+                )
+                .execute()
+            }
+            ("backup", Some(args)) => {
+                BackupCommand::new(client, profile_name, args.value_of("output")).execute()
+            }
+            ("restore", Some(args)) => {
+                let path = args
+                    .value_of("archive")
+                    .expect("argument archive is required");
+                let projects = args
+                    .values_of("project")
+                    .map_or(Vec::new(), |v| v.map(|p| p.to_string()).collect());
+                RestoreCommand::new(
+                    client,
+                    cache,
+                    Journal::load(profile_name)?,
+                    Archive::load(Path::new(path))?,
+                    projects,
+                )
+                .execute()
+            }
+            ("serve-ical", Some(args)) => {
+                let address = format!(
+                    "{}:{}",
+                    args.value_of("address").unwrap_or("127.0.0.1"),
+                    args.value_of("port").unwrap_or("8080")
+                );
+                let project = resolve_arg(&client, &mut cache, args, "projects", "project")?;
+                let query = self.get_query(&client, &mut cache, args)?;
+                ServeIcalCommand::new(
+                    client,
+                    cache,
+                    &address,
+                    args.is_present("events"),
+                    project,
+                    query,
+                )
+                .execute()
+            }
+            ("undo", Some(args)) => {
+                let steps = args
+                    .value_of("steps")
+                    .map_or(1, |s| s.parse::<usize>().unwrap_or(1));
+                UndoCommand::new(client, Journal::load(profile_name)?, steps).execute()
+            }
+            _ => Err(DothisError::UnknownCommand),
+        }
+    }
 
-        match self.command.as_str() {
-            "list" => {
-                let mut table = Table::new();
-                let mut list = ListCommand::new(&self.token, resource_type, table);
+    // The token given as an argument or environment variable takes precedence
+    // over the one configured in the profile, which takes precedence over the
+    // one stored by login
+    fn get_client(&self, name: &str, profile: &Profile) -> Result<TodoistClient, DothisError> {
+        // The token_command of the profile only runs without an explicit token
+        let token = match &self.token {
+            Some(token) => token.clone(),
+            None => match profile.token()? {
+                Some(token) => token,
+                None => match credentials::load_token(name)? {
+                    Some(token) => token,
+                    None => return Err(DothisError::MissingToken),
+                },
+            },
+        };
+
+        match &profile.api_url {
+            Some(url) => Ok(TodoistClient::with_url(&token, url)),
+            None => Ok(TodoistClient::new(&token)),
+        }
+    }
 
-                list.execute()?;
-                Ok(())
+    // Commands like add or edit take the resource as a subcommand
+    fn get_resource_args<'b>(
+        &self,
+        args: &'b ArgMatches<'a>,
+    ) -> Result<(&str, &'b ArgMatches<'a>), DothisError> {
+        match args.subcommand() {
+            (resource, Some(resource_args)) => {
+                Ok((self.get_resource_type(resource)?, resource_args))
             }
-            "add" => {
-                let mut add = AddCommand::new(&self.token, resource_type, self.args.clone());
+            _ => Err(DothisError::UnknownResource),
+        }
+    }
 
-                add.execute()?;
-                Ok(())
+    // Resources are identified by an argument named after them, like task or
+    // project, holding an id or name. Notes have no name, only an id. Names
+    // matched by prefix or fuzzily are confirmed when asked to, unless --yes.
+    fn get_id(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+        resource_type: &str,
+        args: &ArgMatches,
+        confirm: bool,
+    ) -> Result<u32, DothisError> {
+        let name = match resource_type {
+            "tasks" => "task",
+            "projects" => "project",
+            "labels" => "label",
+            _ => return id_of(args, "id").ok_or(DothisError::UnknownResource),
+        };
+        let query = match args.value_of(name) {
+            Some(query) => query,
+            None => return Ok(self.pick(client, cache, resource_type, false)?[0]),
+        };
+        let found = cache.lookup(client, resource_type, query)?;
+        if confirm && !found.exact && !args.is_present("yes") {
+            let resource = format!("{} {}", name, cache.name(resource_type, found.id));
+            if !picker::confirm(&format!("\"{}\" matches {}, go on?", query, resource))? {
+                return Err(DothisError::Unconfirmed(query.to_string(), resource));
             }
-            other => Err(DothisError::UnknownCommand),
+        }
+        Ok(found.id)
+    }
+
+    // Tasks and projects left out of the arguments are picked from the cache
+    // in a terminal, fetched first if it is empty
+    fn pick(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+        resource_type: &str,
+        multiple: bool,
+    ) -> Result<Vec<u32>, DothisError> {
+        let name = resource_type.trim_end_matches('s');
+        if !picker::is_interactive() {
+            return Err(DothisError::MissingArgument(name.to_string()));
+        }
+        if cache.items.is_empty() && cache.projects.is_empty() {
+            cache.refresh(client)?;
+        }
+        let choices = match resource_type {
+            "tasks" => picker::task_choices(cache),
+            "projects" => picker::project_choices(cache),
+            _ => return Err(DothisError::UnknownResource),
+        };
+        match picker::pick(name, &choices, multiple)? {
+            ids if ids.is_empty() => Err(DothisError::NothingPicked),
+            ids => Ok(ids),
+        }
+    }
+
+    // Bulk commands take tasks as arguments or, if none are given, from stdin.
+    // Every task is resolved before sending anything, so a typo fails early.
+    fn get_task_ids(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+        args: &ArgMatches,
+    ) -> Result<Vec<u32>, DothisError> {
+        let queries = match args.values_of("task") {
+            Some(values) => values.map(|v| v.to_string()).collect(),
+            None if picker::is_interactive() => return self.pick(client, cache, "tasks", true),
+            None => read_lines()?,
+        };
+        let mut ids = Vec::new();
+        for query in queries.iter() {
+            ids.push(cache.resolve(client, "tasks", query)?);
+        }
+        Ok(ids)
+    }
+
+    // Saved filters are given by name and evaluated like --query
+    fn get_query(
+        &self,
+        client: &TodoistClient,
+        cache: &mut Cache,
+        args: &ArgMatches,
+    ) -> Result<Option<Query>, DothisError> {
+        if let Some(filter_id) = resolve_arg(client, cache, args, "filters", "filter")? {
+            let query = match cache.filters.iter().find(|f| f.id == filter_id) {
+                Some(filter) => filter.query.clone(),
+                None => return Err(DothisError::UnknownResource),
+            };
+            return Ok(Some(Query::parse(client, cache, &query)?));
+        }
+        match args.value_of("query") {
+            Some(query) => Ok(Some(Query::parse(client, cache, query)?)),
+            None => Ok(None),
         }
     }
 
-    fn get_resource_type(&self) -> Result<&str, DothisError> {
-        match self.resource.as_str() {
+    fn get_resource_type(&self, resource: &str) -> Result<&'static str, DothisError> {
+        match resource {
             "tasks" | "task" => Ok("tasks"),
             "projects" | "project" => Ok("projects"),
             "labels" | "label" => Ok("labels"),
             "notes" | "note" => Ok("notes"),
+            "completed" => Ok("completed"),
             other => return Err(DothisError::UnknownResource),
         }
     }
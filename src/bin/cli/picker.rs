@@ -0,0 +1,351 @@
+use std::io::{self, Read, Write};
+use std::mem;
+
+use dothis::api::resolve::fuzzy_score;
+
+use super::cache::Cache;
+use super::error::DothisError;
+
+// Lines of choices shown at once, and of comments previewed
+const MAX_CHOICES: usize = 10;
+const MAX_PREVIEW: usize = 3;
+
+// Terminal sequences
+const CLEAR_BELOW: &str = "\r\x1b[J";
+const REVERSE: &str = "\x1b[7m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+pub struct Choice {
+    id: u32,
+    // Text the query is matched against, shown with details dimmed
+    text: String,
+    details: String,
+    preview: Vec<String>,
+}
+
+// Commands pick resources instead of taking them as arguments only when
+// someone is there to pick them
+pub fn is_interactive() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO) == 1 && libc::isatty(libc::STDERR_FILENO) == 1 }
+}
+
+// Asks a yes or no question, answered no when nobody is there to answer it
+pub fn confirm(question: &str) -> Result<bool, DothisError> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(["y", "yes"].contains(&answer.trim().to_lowercase().as_str()))
+}
+
+// Open tasks with their project, due date and labels, previewing comments
+pub fn task_choices(cache: &Cache) -> Vec<Choice> {
+    cache
+        .items
+        .iter()
+        .filter(|item| !item.checked && !item.is_deleted)
+        .map(|item| {
+            let mut details = vec![format!("#{}", cache.name("projects", item.project_id))];
+            if let Some(due) = &item.due {
+                details.push(due.string.clone());
+            }
+            details.extend(
+                item.labels
+                    .iter()
+                    .map(|id| format!("@{}", cache.name("labels", *id))),
+            );
+            let preview = cache
+                .notes
+                .iter()
+                .filter(|note| note.item_id == item.id && !note.is_deleted)
+                .map(|note| note.content.replace('\n', " "))
+                .collect();
+            Choice {
+                id: item.id,
+                text: item.content.clone(),
+                details: details.join("  "),
+                preview: preview,
+            }
+        })
+        .collect()
+}
+
+pub fn project_choices(cache: &Cache) -> Vec<Choice> {
+    let resolver = cache.resolver();
+    cache
+        .projects
+        .iter()
+        .filter(|project| !project.is_deleted && !project.is_archived)
+        .map(|project| Choice {
+            id: project.id,
+            text: resolver.project_path(project.id).join("/"),
+            details: String::new(),
+            preview: Vec::new(),
+        })
+        .collect()
+}
+
+// Restores the terminal when picking ends, even early
+struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    fn enable() -> Result<RawMode, DothisError> {
+        let mut termios: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(DothisError::IoError(io::Error::last_os_error()));
+        }
+        let original = termios;
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+        Ok(RawMode { original: original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+fn terminal_width() -> usize {
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+    match unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } {
+        0 if size.ws_col > 0 => size.ws_col as usize,
+        _ => 80,
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+enum Key {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    Tab,
+    Enter,
+    Cancel,
+    Other,
+}
+
+// Keys are read from the terminal without a buffer, which would hold the rest
+// of escape sequences where has_input cannot see it
+struct Keyboard;
+
+impl Read for Keyboard {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = unsafe {
+            libc::read(
+                libc::STDIN_FILENO,
+                buffer.as_mut_ptr() as *mut libc::c_void,
+                buffer.len(),
+            )
+        };
+        match read < 0 {
+            true => Err(io::Error::last_os_error()),
+            false => Ok(read as usize),
+        }
+    }
+}
+
+// Whether more input arrives shortly after an escape
+fn has_input() -> bool {
+    let mut poll = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut poll, 1, 50) > 0 }
+}
+
+fn read_key(stdin: &mut Keyboard) -> Result<Key, DothisError> {
+    let mut byte = [0; 1];
+    stdin.read_exact(&mut byte)?;
+    let key = match byte[0] {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        // Ctrl-C and Ctrl-G cancel, Ctrl-P and Ctrl-N move like arrows
+        0x03 | 0x07 => Key::Cancel,
+        0x10 => Key::Up,
+        0x0e => Key::Down,
+        0x1b => {
+            // Arrows are sent as ESC [ A at once, a lone escape cancels
+            let mut sequence = [0; 2];
+            if !has_input() {
+                return Ok(Key::Cancel);
+            }
+            match stdin.read(&mut sequence[..1])? {
+                1 if sequence[0] == b'[' => {
+                    stdin.read_exact(&mut sequence[1..])?;
+                    match sequence[1] {
+                        b'A' => Key::Up,
+                        b'B' => Key::Down,
+                        _ => Key::Other,
+                    }
+                }
+                _ => Key::Cancel,
+            }
+        }
+        byte if byte < 0x20 => Key::Other,
+        byte => {
+            // Multibyte characters are read up to their last byte
+            let length = match byte {
+                0xf0..=0xff => 4,
+                0xe0..=0xef => 3,
+                0xc0..=0xdf => 2,
+                _ => 1,
+            };
+            let mut bytes = vec![byte; length];
+            stdin.read_exact(&mut bytes[1..])?;
+            match String::from_utf8(bytes).ok().and_then(|s| s.chars().next()) {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        }
+    };
+    Ok(key)
+}
+
+// Choices matching the query, best first, like names are resolved
+fn matching<'a>(choices: &'a [Choice], query: &str) -> Vec<&'a Choice> {
+    if query.is_empty() {
+        return choices.iter().collect();
+    }
+    let query = query.to_lowercase();
+    let mut scored: Vec<(usize, &Choice)> = choices
+        .iter()
+        .filter_map(|choice| {
+            let key = format!("{} {}", choice.text, choice.details).to_lowercase();
+            fuzzy_score(&query, &key).map(|score| (score, choice))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, choice)| choice).collect()
+}
+
+fn render(
+    out: &mut io::Stderr,
+    prompt: &str,
+    query: &str,
+    total: usize,
+    matches: &[&Choice],
+    cursor: usize,
+    selected: &[u32],
+    multiple: bool,
+) -> Result<(), DothisError> {
+    let width = terminal_width().saturating_sub(1);
+    let first = cursor.saturating_sub(MAX_CHOICES - 1);
+    let mut lines = Vec::new();
+    for (i, choice) in matches.iter().enumerate().skip(first).take(MAX_CHOICES) {
+        let mark = match (multiple, selected.contains(&choice.id)) {
+            (true, true) => "* ",
+            (true, false) => "  ",
+            (false, _) => "",
+        };
+        let text = truncate(&format!("{}{}", mark, choice.text), width);
+        let details = truncate(
+            &format!("  {}", choice.details),
+            width.saturating_sub(text.chars().count()),
+        );
+        lines.push(match i == cursor {
+            true => format!("{}{}{}{}{}", REVERSE, text, DIM, details, RESET),
+            false => format!("{}{}{}{}", text, DIM, details, RESET),
+        });
+    }
+    if let Some(choice) = matches.get(cursor) {
+        for comment in choice.preview.iter().take(MAX_PREVIEW) {
+            lines.push(format!(
+                "{}  | {}{}",
+                DIM,
+                truncate(comment, width.saturating_sub(4)),
+                RESET
+            ));
+        }
+    }
+
+    let header = format!("{} {}/{} > {}", prompt, matches.len(), total, query);
+    write!(out, "{}{}", CLEAR_BELOW, header)?;
+    for line in lines.iter() {
+        write!(out, "\r\n{}", line)?;
+    }
+    // The cursor goes back to the end of the query
+    if !lines.is_empty() {
+        write!(out, "\x1b[{}A", lines.len())?;
+    }
+    write!(out, "\r\x1b[{}C", header.chars().count())?;
+    out.flush()?;
+    Ok(())
+}
+
+// Lets the user pick choices by typing part of them. Up and down move, tab
+// selects several when multiple, enter picks the selected ones or the one
+// under the cursor. Nothing is picked when cancelled with escape or Ctrl-C.
+pub fn pick(prompt: &str, choices: &[Choice], multiple: bool) -> Result<Vec<u32>, DothisError> {
+    let _raw = RawMode::enable()?;
+    let mut stdin = Keyboard;
+    let mut out = io::stderr();
+
+    let mut query = String::new();
+    let mut cursor = 0;
+    let mut selected: Vec<u32> = Vec::new();
+    let picked = loop {
+        let matches = matching(choices, &query);
+        cursor = cursor.min(matches.len().saturating_sub(1));
+        render(
+            &mut out,
+            prompt,
+            &query,
+            choices.len(),
+            &matches,
+            cursor,
+            &selected,
+            multiple,
+        )?;
+
+        match read_key(&mut stdin)? {
+            Key::Char(c) => {
+                query.push(c);
+                cursor = 0;
+            }
+            Key::Backspace => {
+                query.pop();
+                cursor = 0;
+            }
+            Key::Up => cursor = cursor.saturating_sub(1),
+            Key::Down => cursor += 1,
+            Key::Tab if multiple => {
+                if let Some(choice) = matches.get(cursor) {
+                    match selected.iter().position(|id| *id == choice.id) {
+                        Some(i) => {
+                            selected.remove(i);
+                        }
+                        None => selected.push(choice.id),
+                    }
+                }
+                cursor += 1;
+            }
+            Key::Enter => match (selected.is_empty(), matches.get(cursor)) {
+                (false, _) => break selected,
+                (true, Some(choice)) => break vec![choice.id],
+                (true, None) => break Vec::new(),
+            },
+            Key::Cancel => break Vec::new(),
+            Key::Tab | Key::Other => (),
+        }
+    };
+
+    write!(out, "{}", CLEAR_BELOW)?;
+    out.flush()?;
+    Ok(picked)
+}
@@ -0,0 +1,97 @@
+use dothis::api::client::TodoistClient;
+use dothis::api::resource::Item;
+
+use super::cache::Cache;
+use super::error::DothisError;
+
+// A subset of the Todoist filter syntax, evaluated against cached tasks.
+// Terms are joined with & and negated with !, like "overdue & !@waiting":
+//   overdue, today, no date, p1 to p4, @label, #project and search: text
+pub struct Query {
+    terms: Vec<(bool, Term)>,
+    // Offset of the timezone of the user from UTC, today is their day
+    utc_offset: Option<i64>,
+}
+
+enum Term {
+    Overdue,
+    Today,
+    NoDate,
+    Priority(i32),
+    Label(u32),
+    Project(u32),
+    // Lowercase text found in the content or description
+    Search(String),
+}
+
+impl Query {
+    pub fn parse(
+        client: &TodoistClient,
+        cache: &mut Cache,
+        query: &str,
+    ) -> Result<Query, DothisError> {
+        let mut terms = Vec::new();
+        for term in query.split('&').map(|t| t.trim()) {
+            let (negated, term) = match term.starts_with('!') {
+                true => (true, term[1..].trim()),
+                false => (false, term),
+            };
+            let parsed = match term.to_lowercase().as_str() {
+                "overdue" => Term::Overdue,
+                "today" => Term::Today,
+                "no date" => Term::NoDate,
+                // p1 is the most urgent priority, which the API calls 4
+                "p1" => Term::Priority(4),
+                "p2" => Term::Priority(3),
+                "p3" => Term::Priority(2),
+                "p4" => Term::Priority(1),
+                _ if term.starts_with('@') => Term::Label(cache.resolve(client, "labels", term)?),
+                _ if term.starts_with('#') => {
+                    Term::Project(cache.resolve(client, "projects", term)?)
+                }
+                lowercase if lowercase.starts_with("search:") => {
+                    Term::Search(lowercase["search:".len()..].trim().to_string())
+                }
+                _ => return Err(DothisError::QueryError(term.to_string())),
+            };
+            terms.push((negated, parsed));
+        }
+
+        Ok(Query {
+            terms: terms,
+            utc_offset: cache.user.as_ref().map(|u| u.utc_offset()),
+        })
+    }
+
+    // Today is taken when matching, queries can outlive a day when serving feeds
+    pub fn matches(&self, task: &Item) -> bool {
+        let now = match self.utc_offset {
+            Some(offset) => time::at_utc(time::get_time() + time::Duration::seconds(offset)),
+            None => time::now(),
+        };
+        let today = time::strftime("%Y-%m-%d", &now).unwrap_or_default();
+        self.terms
+            .iter()
+            .all(|(negated, term)| matches_term(term, task, &today) != *negated)
+    }
+}
+
+fn matches_term(term: &Term, task: &Item, today: &str) -> bool {
+    // Due dates may have a time after the day, which is ignored
+    let day = task
+        .due
+        .as_ref()
+        .map(|d| d.date.get(..10).unwrap_or(&d.date));
+    match term {
+        Term::Overdue => day.map_or(false, |d| d < today),
+        Term::Today => day.map_or(false, |d| d == today),
+        Term::NoDate => day.is_none(),
+        Term::Priority(priority) => task.priority == *priority,
+        Term::Label(id) => task.labels.contains(id),
+        Term::Project(id) => task.project_id == *id,
+        Term::Search(text) => {
+            task.content.to_lowercase().contains(text)
+                || task.description.to_lowercase().contains(text)
+        }
+    }
+}